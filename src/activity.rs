@@ -0,0 +1,184 @@
+use bollard::Docker;
+use bollard::models::ContainerStatsResponse;
+use bollard::query_parameters::StatsOptions;
+use futures::StreamExt;
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+use tokio::time::{Duration, Instant};
+use tracing::{debug, warn};
+
+/// CPU usage, in percent of a single CPU, above which a container counts as active unless
+/// overridden with `--idle-cpu-threshold`.
+pub(crate) const DEFAULT_CPU_THRESHOLD: f64 = 1.0;
+
+/// Network traffic, in bytes per second, above which a container counts as active unless
+/// overridden with `--idle-network-threshold`.
+pub(crate) const DEFAULT_NETWORK_THRESHOLD: u64 = 1024;
+
+/// Criteria for reaping only containers without meaningful activity.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct IdleCriteria<'a> {
+    /// Only containers which have been idle for at least this duration are eligible for reaping.
+    pub(crate) idle_for: Duration,
+    /// CPU usage, in percent of a single CPU, above which a container counts as active.
+    pub(crate) cpu_threshold: f64,
+    /// Network traffic (received and transmitted), in bytes per second, above which a container
+    /// counts as active.
+    pub(crate) network_threshold: u64,
+    /// Activity of containers observed in previous runs.
+    pub(crate) tracker: &'a ActivityTracker,
+}
+
+/// Cumulative resource usage of a container at a point in time.
+#[derive(Debug)]
+struct Sample {
+    taken_at: Instant,
+    /// Total CPU time consumed, in nanoseconds.
+    cpu_usage: u64,
+    /// Total bytes received and transmitted across all networks.
+    network_bytes: u64,
+}
+
+impl Sample {
+    fn new(stats: &ContainerStatsResponse) -> Self {
+        Self {
+            taken_at: Instant::now(),
+            cpu_usage: stats
+                .cpu_stats
+                .as_ref()
+                .and_then(|cpu_stats| cpu_stats.cpu_usage.as_ref())
+                .and_then(|cpu_usage| cpu_usage.total_usage)
+                .unwrap_or_default(),
+            network_bytes: stats
+                .networks
+                .iter()
+                .flat_map(|networks| networks.values())
+                .map(|network| {
+                    network.rx_bytes.unwrap_or_default() + network.tx_bytes.unwrap_or_default()
+                })
+                .sum(),
+        }
+    }
+}
+
+/// Activity of a single container, as observed so far.
+#[derive(Debug)]
+struct Activity {
+    last_sample: Sample,
+    /// When the container was last observed to be active. Containers count as active when they
+    /// are first observed, since their prior activity is unknown.
+    last_active: Instant,
+}
+
+/// Keeps track of container activity across runs, based on periodic samples of their resource
+/// usage.
+#[derive(Debug, Default)]
+pub(crate) struct ActivityTracker {
+    containers: Mutex<HashMap<String, Activity>>,
+}
+
+impl ActivityTracker {
+    /// Records a new sample for a container and returns how long it has been idle.
+    fn record(&self, id: &str, sample: Sample, criteria: &IdleCriteria) -> Duration {
+        let mut containers = self
+            .containers
+            .lock()
+            .expect("activity tracker lock poisoned");
+        let Some(activity) = containers.get_mut(id) else {
+            containers.insert(
+                id.to_string(),
+                Activity {
+                    last_active: sample.taken_at,
+                    last_sample: sample,
+                },
+            );
+            return Duration::ZERO;
+        };
+        let elapsed = sample
+            .taken_at
+            .duration_since(activity.last_sample.taken_at)
+            .as_secs_f64();
+        if elapsed > 0.0 {
+            // Counters are reset when a container restarts, which also counts as activity
+            let cpu_percent = match sample.cpu_usage.checked_sub(activity.last_sample.cpu_usage) {
+                Some(delta) => delta as f64 / 1e9 / elapsed * 100.0,
+                None => f64::INFINITY,
+            };
+            let network_rate = match sample
+                .network_bytes
+                .checked_sub(activity.last_sample.network_bytes)
+            {
+                Some(delta) => delta as f64 / elapsed,
+                None => f64::INFINITY,
+            };
+            if cpu_percent > criteria.cpu_threshold
+                || network_rate > criteria.network_threshold as f64
+            {
+                activity.last_active = sample.taken_at;
+            }
+        }
+        activity.last_sample = sample;
+        activity.last_active.elapsed()
+    }
+
+    /// Forgets about all containers except the given ones.
+    fn retain(&self, ids: &HashSet<&str>) {
+        self.containers
+            .lock()
+            .expect("activity tracker lock poisoned")
+            .retain(|id, _| ids.contains(id.as_str()));
+    }
+}
+
+impl IdleCriteria<'_> {
+    /// Samples the resource usage of the given containers and returns the IDs of those which have
+    /// been idle for at least `idle_for`. Containers whose usage could not be sampled are assumed
+    /// to be active.
+    pub(crate) async fn idle_containers<'a>(
+        &self,
+        docker: &Docker,
+        ids: impl IntoIterator<Item = &'a str>,
+    ) -> HashSet<&'a str> {
+        let ids: HashSet<&str> = ids.into_iter().collect();
+        self.tracker.retain(&ids);
+        let samples = futures::future::join_all(ids.into_iter().map(|id| async move {
+            let stats = docker
+                .stats(
+                    id,
+                    Some(StatsOptions {
+                        stream: false,
+                        one_shot: true,
+                    }),
+                )
+                .next()
+                .await;
+            (id, stats)
+        }))
+        .await;
+        let mut idle_ids = HashSet::new();
+        for (id, stats) in samples {
+            let stats = match stats {
+                Some(Ok(stats)) => stats,
+                Some(Err(e)) => {
+                    warn!("Failed to sample activity of container {}: {}", id, e);
+                    continue;
+                }
+                None => {
+                    warn!("Failed to sample activity of container {}: no stats", id);
+                    continue;
+                }
+            };
+            let idle_time = self.tracker.record(id, Sample::new(&stats), self);
+            if idle_time < self.idle_for {
+                debug!(
+                    "Skipped container {}: idle for only {} seconds",
+                    id,
+                    idle_time.as_secs()
+                );
+                continue;
+            }
+            idle_ids.insert(id);
+        }
+        idle_ids
+    }
+}
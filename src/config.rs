@@ -0,0 +1,168 @@
+use anyhow::Context;
+use bollard::Docker;
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::path::Path;
+use tokio::time::{Duration, Instant, sleep_until};
+use tracing::{Instrument, info, info_span};
+
+use crate::Commands;
+use crate::activity::ActivityTracker;
+use crate::output::Reporter;
+use crate::throttle::Throttle;
+
+/// Contents of a `--config` file.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct Config {
+    /// Reaping rules, evaluated in the order they are defined.
+    #[serde(rename = "rule")]
+    pub(crate) rules: Vec<Rule>,
+}
+
+/// A named reaping rule. Apart from `name` and `every`, each rule accepts the same options as the
+/// subcommand selected by its `type`, using snake_case names (e.g. `min_age`).
+#[derive(Debug, Deserialize)]
+pub(crate) struct Rule {
+    /// Name of the rule, used in log messages.
+    pub(crate) name: String,
+    /// Run the rule repeatedly, waiting this long between removal attempts. Defaults to the global
+    /// `--every` value, if any.
+    #[serde(default, deserialize_with = "crate::deserialize_duration")]
+    pub(crate) every: Option<Duration>,
+    #[serde(flatten)]
+    pub(crate) command: Commands,
+}
+
+impl Config {
+    /// Reads and validates a config file. Rules without `every` run at the global `default_every`
+    /// interval, if any.
+    pub(crate) fn load(
+        path: &Path,
+        default_every: Option<Duration>,
+    ) -> Result<Self, anyhow::Error> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read config file {}", path.display()))?;
+        let config: Config = toml::from_str(&contents)
+            .with_context(|| format!("failed to parse config file {}", path.display()))?;
+        if config.rules.is_empty() {
+            anyhow::bail!("config file {} does not define any rules", path.display());
+        }
+        config.validate(default_every)?;
+        Ok(config)
+    }
+
+    /// Checks that rule names are unique and that each rule's options can be used together.
+    pub(crate) fn validate(&self, default_every: Option<Duration>) -> Result<(), anyhow::Error> {
+        let mut names = HashSet::new();
+        for rule in &self.rules {
+            if !names.insert(rule.name.as_str()) {
+                anyhow::bail!("duplicate rule name in config file: {}", rule.name);
+            }
+            // Idle time is only tracked across repeated runs, so such a rule would never reap
+            if let Commands::Containers(ref args) = rule.command
+                && args.idle_for.is_some()
+                && rule.every.or(default_every).is_none()
+            {
+                anyhow::bail!(
+                    "rule {} sets idle_for, which requires every (or the global --every)",
+                    rule.name
+                );
+            }
+            // Options which the command line only accepts together with another option
+            let dependencies = match rule.command {
+                Commands::Containers(ref args) => vec![
+                    (
+                        "default_ttl",
+                        args.default_ttl.is_some(),
+                        "ttl_label",
+                        args.ttl_label.is_some(),
+                    ),
+                    (
+                        "idle_cpu_threshold",
+                        args.idle_cpu_threshold.is_some(),
+                        "idle_for",
+                        args.idle_for.is_some(),
+                    ),
+                    (
+                        "idle_network_threshold",
+                        args.idle_network_threshold.is_some(),
+                        "idle_for",
+                        args.idle_for.is_some(),
+                    ),
+                    (
+                        "group_by_label",
+                        args.group_by_label.is_some(),
+                        "keep_newest",
+                        args.keep_newest.is_some(),
+                    ),
+                    (
+                        "keep_newest",
+                        args.keep_newest.is_some(),
+                        "group_by_label",
+                        args.group_by_label.is_some(),
+                    ),
+                ],
+                Commands::Networks(ref args) => vec![(
+                    "default_ttl",
+                    args.default_ttl.is_some(),
+                    "ttl_label",
+                    args.ttl_label.is_some(),
+                )],
+                Commands::Volumes(ref args) => vec![(
+                    "default_ttl",
+                    args.default_ttl.is_some(),
+                    "ttl_label",
+                    args.ttl_label.is_some(),
+                )],
+                _ => Vec::new(),
+            };
+            for (option, is_set, required, is_required_set) in dependencies {
+                if is_set && !is_required_set {
+                    anyhow::bail!(
+                        "rule {} sets {}, which requires {}",
+                        rule.name,
+                        option,
+                        required
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Runs each rule once it is due, until no rule is scheduled to run again.
+pub(crate) async fn run(
+    docker: &Docker,
+    rules: &[Rule],
+    default_every: Option<Duration>,
+    dry_run: bool,
+    throttle: &Throttle,
+    reporter: &mut Reporter,
+) {
+    let mut next_runs = vec![Some(Instant::now()); rules.len()];
+    let activities: Vec<ActivityTracker> = rules.iter().map(|_| Default::default()).collect();
+    while let Some(next_run) = next_runs.iter().flatten().min().copied() {
+        sleep_until(next_run).await;
+        for ((rule, next_run), activity) in rules.iter().zip(next_runs.iter_mut()).zip(&activities)
+        {
+            if next_run.is_none_or(|next_run| next_run > Instant::now()) {
+                continue;
+            }
+            let span = info_span!("rule", name = rule.name);
+            async {
+                info!("Starting new run");
+                let started = Instant::now();
+                let result = rule.command.reap(docker, dry_run, activity, throttle).await;
+                reporter.report(result, Some(&rule.name), started.elapsed());
+            }
+            .instrument(span)
+            .await;
+            *next_run = rule
+                .every
+                .or(default_every)
+                .map(|every| Instant::now() + every);
+        }
+    }
+}
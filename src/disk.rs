@@ -0,0 +1,223 @@
+use anyhow::Context;
+use bollard::Docker;
+use bollard::query_parameters::InspectContainerOptions;
+use bollard::service::MountPointTypeEnum;
+use std::cmp::Reverse;
+use std::collections::HashSet;
+use std::io;
+use std::path::{Path, PathBuf};
+use tracing::{debug, info};
+
+use crate::Commands;
+use crate::activity::ActivityTracker;
+use crate::reaper::{ReapError, RemovalOptions, RemovalStatus, Resource, ResourceType};
+use crate::throttle::Throttle;
+
+/// A disk usage threshold beyond which resources are reaped.
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum DiskThreshold {
+    /// Exceeded while more than this percentage of the filesystem is in use.
+    UsageAbove(f64),
+    /// Exceeded while fewer than this many bytes are available.
+    FreeSpaceBelow(u64),
+}
+
+/// Space used and available on a filesystem, in bytes.
+#[derive(Debug)]
+struct DiskUsage {
+    used: u64,
+    /// Space available to unprivileged users, which excludes space reserved for root.
+    available: u64,
+}
+
+impl DiskUsage {
+    /// Reads the usage of the filesystem holding the given path.
+    #[cfg(unix)]
+    // The types of `statvfs` fields differ between platforms
+    #[allow(clippy::useless_conversion)]
+    fn read(path: &Path) -> io::Result<Self> {
+        use std::ffi::CString;
+        use std::os::unix::ffi::OsStrExt;
+
+        let path = CString::new(path.as_os_str().as_bytes())?;
+        let mut stats = std::mem::MaybeUninit::<libc::statvfs>::uninit();
+        // SAFETY: `path` is NUL-terminated and `stats` is valid for writes of a `statvfs`.
+        if unsafe { libc::statvfs(path.as_ptr(), stats.as_mut_ptr()) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        // SAFETY: `statvfs` succeeded, so it has initialized `stats`.
+        let stats = unsafe { stats.assume_init() };
+        let fragment_size = u64::from(stats.f_frsize);
+        Ok(Self {
+            used: u64::from(stats.f_blocks - stats.f_bfree) * fragment_size,
+            available: u64::from(stats.f_bavail) * fragment_size,
+        })
+    }
+
+    #[cfg(not(unix))]
+    fn read(_path: &Path) -> io::Result<Self> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "disk usage can only be determined on Unix",
+        ))
+    }
+
+    /// Percentage of the filesystem in use, computed like `df` does.
+    fn used_percent(&self) -> f64 {
+        let capacity = self.used + self.available;
+        if capacity == 0 {
+            return 0.0;
+        }
+        self.used as f64 / capacity as f64 * 100.0
+    }
+}
+
+/// Restricts reaping to times when the filesystem holding Docker's data root is running out of
+/// space.
+#[derive(Debug)]
+pub(crate) struct DiskGate {
+    threshold: DiskThreshold,
+    data_root: PathBuf,
+}
+
+impl DiskGate {
+    /// Looks up Docker's data root and checks that its disk usage can be determined. The data
+    /// root must be accessible at the same path as on the Docker host.
+    pub(crate) async fn new(docker: &Docker, threshold: DiskThreshold) -> anyhow::Result<Self> {
+        let data_root = docker
+            .info()
+            .await?
+            .docker_root_dir
+            .context("Docker did not report its data root directory")?;
+        let gate = Self {
+            threshold,
+            data_root: PathBuf::from(data_root),
+        };
+        gate.is_exceeded().with_context(|| {
+            format!(
+                "failed to determine disk usage of Docker data root {}",
+                gate.data_root.display()
+            )
+        })?;
+        Ok(gate)
+    }
+
+    /// Whether the filesystem holding Docker's data root is currently beyond the threshold.
+    fn is_exceeded(&self) -> io::Result<bool> {
+        let usage = DiskUsage::read(&self.data_root)?;
+        debug!(
+            "Disk usage of {}: {} bytes used, {} bytes available",
+            self.data_root.display(),
+            usage.used,
+            usage.available
+        );
+        Ok(match self.threshold {
+            DiskThreshold::UsageAbove(percent) => usage.used_percent() > percent,
+            DiskThreshold::FreeSpaceBelow(bytes) => usage.available < bytes,
+        })
+    }
+}
+
+/// Reaps the resources matching the subcommand one by one, oldest first, for as long as the disk
+/// usage threshold is exceeded. In dry-run mode, all matching resources are reported while the
+/// threshold is exceeded, since the effect of removing them cannot be predicted.
+pub(crate) async fn reap_until_relieved(
+    docker: &Docker,
+    command: &Commands,
+    dry_run: bool,
+    activity: &ActivityTracker,
+    throttle: &Throttle,
+    gate: &DiskGate,
+) -> Result<Vec<Resource>, ReapError> {
+    if !gate.is_exceeded().map_err(ReapError::DiskUsage)? {
+        info!("Disk usage within threshold, skipping run");
+        return Ok(Vec::new());
+    }
+    let mut resources = command.reap(docker, true, activity, throttle).await?;
+    resources.sort_by_key(|resource| Reverse(resource.age));
+    if dry_run {
+        return Ok(resources);
+    }
+    let options = command.removal_options(throttle);
+    remove_until_relieved(docker, command, &options, resources, || {
+        gate.is_exceeded().map_err(ReapError::DiskUsage)
+    })
+    .await
+}
+
+/// Removes the resources matching the subcommand in the given order for as long as `is_exceeded`
+/// holds. The networks and volumes reported along with containers can only be removed after
+/// the containers themselves, so they are removed at the end if (and only if) they were attached
+/// to a container removed here, regardless of the threshold.
+pub(crate) async fn remove_until_relieved(
+    docker: &Docker,
+    command: &Commands,
+    options: &RemovalOptions<'_>,
+    resources: Vec<Resource>,
+    mut is_exceeded: impl FnMut() -> Result<bool, ReapError>,
+) -> Result<Vec<Resource>, ReapError> {
+    let (attached, resources): (Vec<Resource>, Vec<Resource>) = match command {
+        Commands::Containers(_) => resources.into_iter().partition(|resource| {
+            matches!(
+                resource.resource_type,
+                ResourceType::Network | ResourceType::Volume
+            )
+        }),
+        _ => (Vec::new(), resources),
+    };
+    let mut attached_resources = HashSet::new();
+    let mut removed_resources = Vec::new();
+    for mut resource in resources {
+        if !is_exceeded()? {
+            info!("Disk usage back within threshold");
+            break;
+        }
+        // Attachments can no longer be looked up once the container has been removed
+        let attachments = match resource.resource_type {
+            ResourceType::Container => attachments(docker, &resource.id).await,
+            _ => Vec::new(),
+        };
+        resource.remove(docker, options).await;
+        if matches!(resource.status, RemovalStatus::Success) {
+            attached_resources.extend(attachments);
+        }
+        removed_resources.push(resource);
+    }
+    for mut resource in attached {
+        if !attached_resources.contains(&(resource.resource_type, resource.id.clone())) {
+            continue;
+        }
+        resource.remove(docker, options).await;
+        removed_resources.push(resource);
+    }
+    Ok(removed_resources)
+}
+
+/// Networks and volumes attached to the container, identified by name as in the resources
+/// returned by `reap_containers`.
+async fn attachments(docker: &Docker, container_id: &str) -> Vec<(ResourceType, String)> {
+    let container = match docker
+        .inspect_container(container_id, None::<InspectContainerOptions>)
+        .await
+    {
+        Ok(container) => container,
+        Err(e) => {
+            debug!("Failed to inspect container {}: {}", container_id, e);
+            return Vec::new();
+        }
+    };
+    let networks = container
+        .network_settings
+        .and_then(|network_settings| network_settings.networks)
+        .unwrap_or_default()
+        .into_keys()
+        .map(|name| (ResourceType::Network, name));
+    let volumes = container
+        .mounts
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|mount| mount.typ == Some(MountPointTypeEnum::VOLUME))
+        .filter_map(|mount| mount.name)
+        .map(|name| (ResourceType::Volume, name));
+    networks.chain(volumes).collect()
+}
@@ -0,0 +1,265 @@
+use std::iter::Peekable;
+use std::str::FromStr;
+use thiserror::Error;
+use tokio::time::Duration;
+
+use crate::reaper::{Listed, NamePattern};
+
+/// A boolean expression over the properties of a listed resource, such as
+/// `label:team=* and (status:exited or age>2h) and not label:pinned`.
+///
+/// Predicates are combined with `and`, `or`, and `not` (in decreasing order of precedence) and
+/// grouped with parentheses. The following predicates are supported:
+///
+/// - `label:KEY` matches resources with the label, `label:KEY=VALUE` those whose label value
+///   matches the glob pattern `VALUE`
+/// - `name:PATTERN` matches resources with any name matching the glob pattern
+/// - `id:PREFIX` matches resources whose ID starts with the prefix
+/// - `status:STATUS` matches containers in the given state (e.g. `running` or `exited`)
+/// - `age>DURATION`, `age>=DURATION`, `age<DURATION`, and `age<=DURATION` compare the age of
+///   resources
+#[derive(Clone, Debug)]
+pub(crate) enum MatchExpression {
+    And(Box<MatchExpression>, Box<MatchExpression>),
+    Or(Box<MatchExpression>, Box<MatchExpression>),
+    Not(Box<MatchExpression>),
+    Predicate(Predicate),
+}
+
+/// A single condition within a [`MatchExpression`].
+#[derive(Clone, Debug)]
+pub(crate) enum Predicate {
+    Label {
+        key: String,
+        value: Option<NamePattern>,
+    },
+    Name(NamePattern),
+    Id(String),
+    Status(String),
+    Age(Comparison, Duration),
+}
+
+/// Operator of an `age` predicate.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Comparison {
+    Greater,
+    GreaterOrEqual,
+    Less,
+    LessOrEqual,
+}
+
+/// Error encountered while parsing a [`MatchExpression`].
+#[derive(Error, Debug)]
+pub(crate) enum ParseError {
+    #[error("unexpected end of expression")]
+    UnexpectedEnd,
+    #[error("unexpected {0:?}")]
+    UnexpectedToken(String),
+    #[error("unknown predicate {0:?}, expected label:, name:, id:, status:, or age")]
+    UnknownPredicate(String),
+    #[error("empty value in predicate {0:?}")]
+    EmptyValue(String),
+    #[error("invalid duration in predicate {0:?}")]
+    InvalidDuration(String),
+    #[error("invalid pattern in predicate {0:?}")]
+    InvalidPattern(String),
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Token<'a> {
+    LeftParen,
+    RightParen,
+    Word(&'a str),
+}
+
+/// Splits an expression into parentheses and whitespace-separated words.
+fn tokenize(input: &str) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    let mut word_start = None;
+    for (i, c) in input.char_indices() {
+        if c.is_whitespace() || c == '(' || c == ')' {
+            if let Some(start) = word_start.take() {
+                tokens.push(Token::Word(&input[start..i]));
+            }
+            match c {
+                '(' => tokens.push(Token::LeftParen),
+                ')' => tokens.push(Token::RightParen),
+                _ => {}
+            }
+        } else if word_start.is_none() {
+            word_start = Some(i);
+        }
+    }
+    if let Some(start) = word_start {
+        tokens.push(Token::Word(&input[start..]));
+    }
+    tokens
+}
+
+/// Recursive descent parser over the tokens of an expression.
+struct Parser<'a, I: Iterator<Item = Token<'a>>> {
+    tokens: Peekable<I>,
+}
+
+impl<'a, I: Iterator<Item = Token<'a>>> Parser<'a, I> {
+    fn next_if_keyword(&mut self, keyword: &str) -> bool {
+        self.tokens
+            .next_if(
+                |token| matches!(token, Token::Word(word) if word.eq_ignore_ascii_case(keyword)),
+            )
+            .is_some()
+    }
+
+    fn parse_or(&mut self) -> Result<MatchExpression, ParseError> {
+        let mut expression = self.parse_and()?;
+        while self.next_if_keyword("or") {
+            expression = MatchExpression::Or(Box::new(expression), Box::new(self.parse_and()?));
+        }
+        Ok(expression)
+    }
+
+    fn parse_and(&mut self) -> Result<MatchExpression, ParseError> {
+        let mut expression = self.parse_not()?;
+        while self.next_if_keyword("and") {
+            expression = MatchExpression::And(Box::new(expression), Box::new(self.parse_not()?));
+        }
+        Ok(expression)
+    }
+
+    fn parse_not(&mut self) -> Result<MatchExpression, ParseError> {
+        if self.next_if_keyword("not") {
+            return Ok(MatchExpression::Not(Box::new(self.parse_not()?)));
+        }
+        match self.tokens.next() {
+            Some(Token::LeftParen) => {
+                let expression = self.parse_or()?;
+                match self.tokens.next() {
+                    Some(Token::RightParen) => Ok(expression),
+                    Some(token) => Err(ParseError::UnexpectedToken(token.to_string())),
+                    None => Err(ParseError::UnexpectedEnd),
+                }
+            }
+            Some(Token::Word(word)) => Ok(MatchExpression::Predicate(word.parse()?)),
+            Some(token) => Err(ParseError::UnexpectedToken(token.to_string())),
+            None => Err(ParseError::UnexpectedEnd),
+        }
+    }
+}
+
+impl std::fmt::Display for Token<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::LeftParen => write!(f, "("),
+            Self::RightParen => write!(f, ")"),
+            Self::Word(word) => write!(f, "{}", word),
+        }
+    }
+}
+
+impl FromStr for MatchExpression {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser {
+            tokens: tokenize(s).into_iter().peekable(),
+        };
+        let expression = parser.parse_or()?;
+        match parser.tokens.next() {
+            Some(token) => Err(ParseError::UnexpectedToken(token.to_string())),
+            None => Ok(expression),
+        }
+    }
+}
+
+impl FromStr for Predicate {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(comparison) = s.strip_prefix("age") {
+            let (comparison, duration) = if let Some(duration) = comparison.strip_prefix(">=") {
+                (Comparison::GreaterOrEqual, duration)
+            } else if let Some(duration) = comparison.strip_prefix("<=") {
+                (Comparison::LessOrEqual, duration)
+            } else if let Some(duration) = comparison.strip_prefix('>') {
+                (Comparison::Greater, duration)
+            } else if let Some(duration) = comparison.strip_prefix('<') {
+                (Comparison::Less, duration)
+            } else {
+                return Err(ParseError::UnknownPredicate(s.to_string()));
+            };
+            let duration = crate::parse_duration(duration)
+                .map_err(|_| ParseError::InvalidDuration(s.to_string()))?;
+            return Ok(Self::Age(comparison, duration));
+        }
+        let Some((field, value)) = s.split_once(':') else {
+            return Err(ParseError::UnknownPredicate(s.to_string()));
+        };
+        if value.is_empty() {
+            return Err(ParseError::EmptyValue(s.to_string()));
+        }
+        let glob = |pattern| {
+            NamePattern::glob(pattern).map_err(|_| ParseError::InvalidPattern(s.to_string()))
+        };
+        match field {
+            "label" => match value.split_once('=') {
+                Some(("", _)) => Err(ParseError::EmptyValue(s.to_string())),
+                Some((key, value)) => Ok(Self::Label {
+                    key: key.to_string(),
+                    value: Some(glob(value)?),
+                }),
+                None => Ok(Self::Label {
+                    key: value.to_string(),
+                    value: None,
+                }),
+            },
+            "name" => Ok(Self::Name(glob(value)?)),
+            "id" => Ok(Self::Id(value.to_string())),
+            "status" => Ok(Self::Status(value.to_string())),
+            _ => Err(ParseError::UnknownPredicate(s.to_string())),
+        }
+    }
+}
+
+impl MatchExpression {
+    /// Whether the listed resource satisfies the expression.
+    pub(crate) fn matches<T: Listed>(&self, resource: &T) -> bool {
+        match self {
+            Self::And(left, right) => left.matches(resource) && right.matches(resource),
+            Self::Or(left, right) => left.matches(resource) || right.matches(resource),
+            Self::Not(expression) => !expression.matches(resource),
+            Self::Predicate(predicate) => predicate.matches(resource),
+        }
+    }
+}
+
+impl Predicate {
+    fn matches<T: Listed>(&self, resource: &T) -> bool {
+        match self {
+            Self::Label { key, value } => {
+                let label_value = resource.labels().and_then(|labels| labels.get(key));
+                match (label_value, value) {
+                    (Some(label_value), Some(pattern)) => pattern.is_match(label_value),
+                    (Some(_), None) => true,
+                    (None, _) => false,
+                }
+            }
+            Self::Name(pattern) => resource
+                .names()
+                .into_iter()
+                .any(|name| pattern.is_match(name)),
+            Self::Id(prefix) => resource
+                .id()
+                .is_some_and(|id| id.starts_with(prefix.as_str())),
+            Self::Status(status) => resource
+                .status()
+                .is_some_and(|resource_status| resource_status.eq_ignore_ascii_case(status)),
+            // Resources of unknown age never satisfy an age comparison
+            Self::Age(comparison, duration) => resource.age().is_some_and(|age| match comparison {
+                Comparison::Greater => age > *duration,
+                Comparison::GreaterOrEqual => age >= *duration,
+                Comparison::Less => age < *duration,
+                Comparison::LessOrEqual => age <= *duration,
+            }),
+        }
+    }
+}
@@ -1,19 +1,40 @@
+mod activity;
+mod config;
+mod disk;
+mod expression;
+mod monitoring;
+mod output;
 mod reaper;
+mod throttle;
+mod watch;
 
 #[cfg(test)]
 mod tests;
 
 use std::env;
-use tracing::{debug, error, info, warn};
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::process::ExitCode;
+use tracing::{debug, info, warn};
 
+use activity::{ActivityTracker, DEFAULT_CPU_THRESHOLD, DEFAULT_NETWORK_THRESHOLD, IdleCriteria};
 use anyhow::Context;
 use bollard::Docker;
-use clap::{Args, Parser, Subcommand};
+use clap::{Args, CommandFactory, Parser, Subcommand};
+use config::Config;
+use disk::{DiskGate, DiskThreshold, reap_until_relieved};
+use expression::MatchExpression;
+use output::{OutputFormat, Reporter};
 use reaper::{
-    Filter, ReapContainersConfig, ReapNetworksConfig, ReapVolumesConfig, reap_containers,
-    reap_networks, reap_volumes,
+    BuildCacheAgeFrom, ContainerAgeFrom, DEFAULT_PROTECTION_LABEL, Filter, NamePattern,
+    ProtectionLabel, ReapBuildCacheConfig, ReapContainersConfig, ReapError, ReapImagesConfig,
+    ReapNetworksConfig, ReapVolumesConfig, ReapVolumesMode, RemovalLimit, RemovalOptions, Resource,
+    reap_build_cache, reap_containers, reap_images, reap_networks, reap_volumes,
 };
-use tokio::time::{Duration, sleep};
+use serde::{Deserialize, Deserializer};
+use throttle::{MIN_RATE, Throttle};
+use tokio::time::{Duration, Instant, sleep};
+use watch::{DEFAULT_RESYNC_INTERVAL, watch};
 
 #[derive(Debug, Parser)]
 #[command(
@@ -23,16 +44,65 @@ use tokio::time::{Duration, sleep};
 )]
 struct Cli {
     #[command(subcommand)]
-    command: Commands,
-    /// Run repeatedly, waiting this long between removal attempts.
+    command: Option<Commands>,
+    /// Run the reaping rules defined in this TOML file instead of a single subcommand.
+    #[arg(long, value_name = "path", conflicts_with = "watch")]
+    config: Option<PathBuf>,
+    /// Run repeatedly, waiting this long between removal attempts. With --watch, the interval
+    /// between full resyncs (default: 5m).
     #[arg(long, value_name = "duration", value_parser = parse_duration, global = true)]
     every: Option<Duration>,
+    /// Watch Docker events and reap each container, network, or volume as soon as it expires.
+    #[arg(long, global = true)]
+    watch: bool,
     /// Log output without actually removing resources.
     #[arg(long, short = 'd', global = true)]
     dry_run: bool,
+    /// Treat resources whose removal was already in progress as failures when determining the exit
+    /// code.
+    #[arg(long, global = true)]
+    in_progress_is_failure: bool,
+    /// Only reap while more than this percentage of the filesystem holding Docker's data root is
+    /// in use, removing the oldest matching resources first.
+    #[arg(
+        long,
+        value_name = "percent",
+        value_parser = parse_percent,
+        global = true,
+        conflicts_with = "free_space_below"
+    )]
+    when_disk_usage_above: Option<f64>,
+    /// Only reap while fewer than this many bytes are available on the filesystem holding Docker's
+    /// data root, removing the oldest matching resources first.
+    #[arg(long, value_name = "bytes", global = true)]
+    free_space_below: Option<u64>,
+    /// Maximum number of removals in flight at once, across all resource types.
+    #[arg(
+        long,
+        value_name = "n",
+        value_parser = clap::value_parser!(u32).range(1..),
+        global = true
+    )]
+    concurrency: Option<u32>,
+    /// Maximum number of removals started per second, across all resource types.
+    #[arg(long, value_name = "per-second", value_parser = parse_rate, global = true)]
+    rate: Option<f64>,
+    /// Serve Prometheus metrics over HTTP on this address (e.g. 0.0.0.0:9090).
+    #[arg(long, value_name = "address", global = true)]
+    metrics_listen: Option<SocketAddr>,
+    /// Format of the report written to stdout after each run.
+    #[arg(
+        long,
+        value_name = "format",
+        value_enum,
+        default_value_t,
+        global = true
+    )]
+    output: OutputFormat,
 }
 
-#[derive(Debug, Subcommand)]
+#[derive(Debug, Subcommand, Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
 enum Commands {
     /// Reap matching containers.
     Containers(ContainersArgs),
@@ -40,17 +110,27 @@ enum Commands {
     Networks(NetworksArgs),
     /// Reap matching volumes.
     Volumes(VolumesArgs),
+    /// Reap matching images not used by any container.
+    Images(ImagesArgs),
+    /// Reap unused BuildKit build cache records.
+    BuildCache(BuildCacheArgs),
 }
 
-#[derive(Debug, Args)]
+#[derive(Debug, Default, Args, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 #[command(after_help = "Note: <duration> values accept Go-style duration strings (e.g. 1m30s)")]
 struct ContainersArgs {
     /// Only reap containers older than this duration.
+    #[serde(deserialize_with = "deserialize_duration")]
     #[arg(long, value_name = "duration", value_parser = parse_duration)]
     min_age: Option<Duration>,
     /// Only reap containers younger than this duration.
+    #[serde(deserialize_with = "deserialize_duration")]
     #[arg(long, value_name = "duration", value_parser = parse_duration)]
     max_age: Option<Duration>,
+    /// Timestamp from which the age of containers is measured.
+    #[arg(long, value_name = "timestamp", value_enum, default_value_t)]
+    age_from: ContainerAgeFrom,
     #[arg(
         name = "filter",
         long,
@@ -60,19 +140,112 @@ struct ContainersArgs {
         value_name = "name=value",
         value_parser = parse_filter
     )]
+    #[serde(deserialize_with = "deserialize_filters")]
     filters: Vec<Filter>,
+    /// Only reap containers whose entire name matches this regular expression. Can be specified
+    /// multiple times.
+    #[serde(deserialize_with = "deserialize_name_regexes")]
+    #[arg(long, value_name = "pattern", value_parser = parse_name_regex)]
+    name_regex: Vec<NamePattern>,
+    /// Only reap containers whose entire name matches this glob pattern, in which `*` matches any
+    /// sequence of characters and `?` any single character. Can be specified multiple times.
+    #[serde(deserialize_with = "deserialize_name_globs")]
+    #[arg(long, value_name = "pattern", value_parser = parse_name_glob)]
+    name_glob: Vec<NamePattern>,
+    /// Never reap containers matching this filter, even if they match `--filter`. Supports the
+    /// `id`, `label`, and `name` filters. Can be specified multiple times.
+    #[arg(name = "exclude", long, value_name = "name=value", value_parser = parse_exclude)]
+    #[serde(deserialize_with = "deserialize_excludes")]
+    excludes: Vec<Filter>,
+    /// Only reap containers satisfying this expression of `label:`, `name:`, `id:`, `status:`, and
+    /// `age` predicates combined with `and`, `or`, `not`, and parentheses (e.g. `label:team=* and
+    /// (status:exited or age>2h)`).
+    #[arg(name = "match", long, value_name = "expression")]
+    #[serde(rename = "match", deserialize_with = "deserialize_match_expression")]
+    match_expression: Option<MatchExpression>,
+    /// Read each container's time-to-live from this label, reaping it once older than its TTL.
+    #[arg(long, value_name = "label")]
+    ttl_label: Option<String>,
+    /// TTL for containers without the TTL label. If unset, unlabeled containers are not reaped.
+    #[serde(deserialize_with = "deserialize_duration")]
+    #[arg(long, value_name = "duration", value_parser = parse_duration, requires = "ttl_label")]
+    default_ttl: Option<Duration>,
+    /// Read each container's expiration time (RFC3339 or UNIX timestamp) from this label, reaping
+    /// it once expired. Takes precedence over the TTL label.
+    #[arg(long, value_name = "label")]
+    expiry_label: Option<String>,
     /// Also attempt to remove the networks associated with reaped containers.
     #[arg(long)]
     reap_networks: bool,
+    /// Never remove the network with this name, in addition to Docker's predefined networks
+    /// (bridge, host, none, ingress, and docker_gwbridge). Can be specified multiple times.
+    #[arg(long = "protect-network", value_name = "name")]
+    protected_networks: Vec<String>,
+    /// Never remove containers with this label, or networks or volumes with it attached to reaped
+    /// containers, given as KEY=VALUE or KEY (default: docker-reaper.protect=true).
+    #[serde(deserialize_with = "deserialize_protection_label")]
+    #[arg(long, value_name = "label", value_parser = parse_protection_label)]
+    protection_label: Option<ProtectionLabel>,
+    /// Also attempt to remove the volumes mounted by reaped containers: only anonymous volumes
+    /// (the default), or all volumes.
+    #[arg(
+        long,
+        value_name = "mode",
+        value_enum,
+        num_args = 0..=1,
+        default_missing_value = "anonymous"
+    )]
+    reap_volumes: Option<ReapVolumesMode>,
+    /// Stop containers before removing them, killing them if they have not exited after this
+    /// duration (rounded up to whole seconds).
+    #[serde(deserialize_with = "deserialize_duration")]
+    #[arg(long, value_name = "duration", value_parser = parse_duration)]
+    stop_timeout: Option<Duration>,
+    /// Stop containers with this signal (e.g. SIGTERM) before removing them, instead of their
+    /// configured stop signal.
+    #[arg(long, value_name = "signal")]
+    stop_signal: Option<String>,
+    /// Only reap containers without meaningful CPU or network activity for at least this duration.
+    /// Activity is sampled once per run, so this requires --every.
+    #[serde(deserialize_with = "deserialize_duration")]
+    #[arg(long, value_name = "duration", value_parser = parse_duration)]
+    idle_for: Option<Duration>,
+    /// CPU usage, in percent of a single CPU, above which a container counts as active (default:
+    /// 1).
+    #[arg(long, value_name = "percent", requires = "idle_for")]
+    idle_cpu_threshold: Option<f64>,
+    /// Network traffic, in bytes per second, above which a container counts as active (default:
+    /// 1024).
+    #[arg(long, value_name = "bytes", requires = "idle_for")]
+    idle_network_threshold: Option<u64>,
+    /// Group containers by the value of this label and only reap those which are not among the
+    /// newest in their group. Containers without the label are not reaped.
+    #[arg(long, value_name = "label", requires = "keep_newest")]
+    group_by_label: Option<String>,
+    /// Number of newest containers to keep in each group.
+    #[arg(long, value_name = "n", requires = "group_by_label")]
+    keep_newest: Option<usize>,
+    /// If more than this many containers match, reap the oldest regardless of their age until only
+    /// this many remain.
+    #[arg(long, value_name = "n")]
+    max_count: Option<usize>,
+    /// Abort the run without removing anything if more than this many containers would be removed,
+    /// or more than this percentage of all containers if suffixed with %.
+    #[serde(deserialize_with = "deserialize_removal_limit")]
+    #[arg(long, value_name = "n|percent%", value_parser = parse_removal_limit)]
+    max_removals: Option<RemovalLimit>,
 }
 
-#[derive(Debug, Args)]
+#[derive(Debug, Default, Args, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 #[command(after_help = "Note: <duration> values accept Go-style duration strings (e.g. 1m30s)")]
 struct NetworksArgs {
     /// Only reap networks older than this duration.
+    #[serde(deserialize_with = "deserialize_duration")]
     #[arg(long, value_name = "duration", value_parser = parse_duration)]
     min_age: Option<Duration>,
     /// Only reap networks younger than this duration.
+    #[serde(deserialize_with = "deserialize_duration")]
     #[arg(long, value_name = "duration", value_parser = parse_duration)]
     max_age: Option<Duration>,
     #[arg(
@@ -83,16 +256,73 @@ struct NetworksArgs {
         value_name = "name=value",
         value_parser = parse_filter
     )]
+    #[serde(deserialize_with = "deserialize_filters")]
     filters: Vec<Filter>,
+    /// Only reap networks whose entire name matches this regular expression. Can be specified
+    /// multiple times.
+    #[serde(deserialize_with = "deserialize_name_regexes")]
+    #[arg(long, value_name = "pattern", value_parser = parse_name_regex)]
+    name_regex: Vec<NamePattern>,
+    /// Only reap networks whose entire name matches this glob pattern, in which `*` matches any
+    /// sequence of characters and `?` any single character. Can be specified multiple times.
+    #[serde(deserialize_with = "deserialize_name_globs")]
+    #[arg(long, value_name = "pattern", value_parser = parse_name_glob)]
+    name_glob: Vec<NamePattern>,
+    /// Never reap networks matching this filter, even if they match `--filter`. Supports the
+    /// `id`, `label`, and `name` filters. Can be specified multiple times.
+    #[arg(name = "exclude", long, value_name = "name=value", value_parser = parse_exclude)]
+    #[serde(deserialize_with = "deserialize_excludes")]
+    excludes: Vec<Filter>,
+    /// Only reap networks satisfying this expression of `label:`, `name:`, `id:`, `status:`, and
+    /// `age` predicates combined with `and`, `or`, `not`, and parentheses (e.g. `label:team=* and
+    /// (status:exited or age>2h)`).
+    #[arg(name = "match", long, value_name = "expression")]
+    #[serde(rename = "match", deserialize_with = "deserialize_match_expression")]
+    match_expression: Option<MatchExpression>,
+    /// Read each network's time-to-live from this label, reaping it once older than its TTL.
+    #[arg(long, value_name = "label")]
+    ttl_label: Option<String>,
+    /// TTL for networks without the TTL label. If unset, unlabeled networks are not reaped.
+    #[serde(deserialize_with = "deserialize_duration")]
+    #[arg(long, value_name = "duration", value_parser = parse_duration, requires = "ttl_label")]
+    default_ttl: Option<Duration>,
+    /// Read each network's expiration time (RFC3339 or UNIX timestamp) from this label, reaping it
+    /// once expired. Takes precedence over the TTL label.
+    #[arg(long, value_name = "label")]
+    expiry_label: Option<String>,
+    /// Never remove the network with this name, in addition to Docker's predefined networks
+    /// (bridge, host, none, ingress, and docker_gwbridge). Can be specified multiple times.
+    #[arg(long = "protect-network", value_name = "name")]
+    protected_networks: Vec<String>,
+    /// Never remove networks with this label, given as KEY=VALUE or KEY (default:
+    /// docker-reaper.protect=true).
+    #[serde(deserialize_with = "deserialize_protection_label")]
+    #[arg(long, value_name = "label", value_parser = parse_protection_label)]
+    protection_label: Option<ProtectionLabel>,
+    /// Only reap networks without any connected containers.
+    #[arg(long)]
+    unused_only: bool,
+    /// If more than this many networks match, reap the oldest regardless of their age until only
+    /// this many remain.
+    #[arg(long, value_name = "n")]
+    max_count: Option<usize>,
+    /// Abort the run without removing anything if more than this many networks would be removed,
+    /// or more than this percentage of all networks if suffixed with %.
+    #[serde(deserialize_with = "deserialize_removal_limit")]
+    #[arg(long, value_name = "n|percent%", value_parser = parse_removal_limit)]
+    max_removals: Option<RemovalLimit>,
 }
 
-#[derive(Debug, Args)]
+#[derive(Debug, Default, Args, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 #[command(after_help = "Note: <duration> values accept Go-style duration strings (e.g. 1m30s)")]
 struct VolumesArgs {
     /// Only reap volumes older than this duration.
+    #[serde(deserialize_with = "deserialize_duration")]
     #[arg(long, value_name = "duration", value_parser = parse_duration)]
     min_age: Option<Duration>,
     /// Only reap volumes younger than this duration.
+    #[serde(deserialize_with = "deserialize_duration")]
     #[arg(long, value_name = "duration", value_parser = parse_duration)]
     max_age: Option<Duration>,
     #[arg(
@@ -103,7 +333,261 @@ struct VolumesArgs {
         value_name = "name=value",
         value_parser = parse_filter
     )]
+    #[serde(deserialize_with = "deserialize_filters")]
+    filters: Vec<Filter>,
+    /// Only reap volumes whose entire name matches this regular expression. Can be specified
+    /// multiple times.
+    #[serde(deserialize_with = "deserialize_name_regexes")]
+    #[arg(long, value_name = "pattern", value_parser = parse_name_regex)]
+    name_regex: Vec<NamePattern>,
+    /// Only reap volumes whose entire name matches this glob pattern, in which `*` matches any
+    /// sequence of characters and `?` any single character. Can be specified multiple times.
+    #[serde(deserialize_with = "deserialize_name_globs")]
+    #[arg(long, value_name = "pattern", value_parser = parse_name_glob)]
+    name_glob: Vec<NamePattern>,
+    /// Never reap volumes matching this filter, even if they match `--filter`. Supports the
+    /// `id`, `label`, and `name` filters. Can be specified multiple times.
+    #[arg(name = "exclude", long, value_name = "name=value", value_parser = parse_exclude)]
+    #[serde(deserialize_with = "deserialize_excludes")]
+    excludes: Vec<Filter>,
+    /// Only reap volumes satisfying this expression of `label:`, `name:`, `id:`, `status:`, and
+    /// `age` predicates combined with `and`, `or`, `not`, and parentheses (e.g. `label:team=* and
+    /// (status:exited or age>2h)`).
+    #[arg(name = "match", long, value_name = "expression")]
+    #[serde(rename = "match", deserialize_with = "deserialize_match_expression")]
+    match_expression: Option<MatchExpression>,
+    /// Read each volume's time-to-live from this label, reaping it once older than its TTL.
+    #[arg(long, value_name = "label")]
+    ttl_label: Option<String>,
+    /// TTL for volumes without the TTL label. If unset, unlabeled volumes are not reaped.
+    #[serde(deserialize_with = "deserialize_duration")]
+    #[arg(long, value_name = "duration", value_parser = parse_duration, requires = "ttl_label")]
+    default_ttl: Option<Duration>,
+    /// Read each volume's expiration time (RFC3339 or UNIX timestamp) from this label, reaping it
+    /// once expired. Takes precedence over the TTL label.
+    #[arg(long, value_name = "label")]
+    expiry_label: Option<String>,
+    /// Never remove volumes with this label, given as KEY=VALUE or KEY (default:
+    /// docker-reaper.protect=true).
+    #[serde(deserialize_with = "deserialize_protection_label")]
+    #[arg(long, value_name = "label", value_parser = parse_protection_label)]
+    protection_label: Option<ProtectionLabel>,
+    /// Only reap volumes which are not mounted by any container.
+    #[arg(long)]
+    unused_only: bool,
+    /// If more than this many volumes match, reap the oldest regardless of their age until only
+    /// this many remain.
+    #[arg(long, value_name = "n")]
+    max_count: Option<usize>,
+    /// Abort the run without removing anything if more than this many volumes would be removed,
+    /// or more than this percentage of all volumes if suffixed with %.
+    #[serde(deserialize_with = "deserialize_removal_limit")]
+    #[arg(long, value_name = "n|percent%", value_parser = parse_removal_limit)]
+    max_removals: Option<RemovalLimit>,
+}
+
+#[derive(Debug, Default, Args, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+#[command(after_help = "Note: <duration> values accept Go-style duration strings (e.g. 1m30s)")]
+struct ImagesArgs {
+    /// Only reap images older than this duration.
+    #[serde(deserialize_with = "deserialize_duration")]
+    #[arg(long, value_name = "duration", value_parser = parse_duration)]
+    min_age: Option<Duration>,
+    /// Only reap images younger than this duration.
+    #[serde(deserialize_with = "deserialize_duration")]
+    #[arg(long, value_name = "duration", value_parser = parse_duration)]
+    max_age: Option<Duration>,
+    #[arg(
+        name = "filter",
+        long,
+        short = 'f',
+        help = "Only reap images matching a Docker Engine-supported filter (https://docs.docker.com/engine/reference/commandline/images/#filter). Can be specified multiple times",
+        value_name = "name=value",
+        value_parser = parse_filter
+    )]
+    #[serde(deserialize_with = "deserialize_filters")]
     filters: Vec<Filter>,
+    /// Remove images even if they are referenced by multiple tags.
+    #[arg(long)]
+    force: bool,
+    /// Never remove images with this label, given as KEY=VALUE or KEY (default:
+    /// docker-reaper.protect=true).
+    #[serde(deserialize_with = "deserialize_protection_label")]
+    #[arg(long, value_name = "label", value_parser = parse_protection_label)]
+    protection_label: Option<ProtectionLabel>,
+    /// Abort the run without removing anything if more than this many images would be removed,
+    /// or more than this percentage of all images if suffixed with %.
+    #[serde(deserialize_with = "deserialize_removal_limit")]
+    #[arg(long, value_name = "n|percent%", value_parser = parse_removal_limit)]
+    max_removals: Option<RemovalLimit>,
+}
+
+#[derive(Debug, Default, Args, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+#[command(after_help = "Note: <duration> values accept Go-style duration strings (e.g. 1m30s)")]
+struct BuildCacheArgs {
+    /// Only reap build cache records older than this duration.
+    #[serde(deserialize_with = "deserialize_duration")]
+    #[arg(long, value_name = "duration", value_parser = parse_duration)]
+    min_age: Option<Duration>,
+    /// Only reap build cache records younger than this duration.
+    #[serde(deserialize_with = "deserialize_duration")]
+    #[arg(long, value_name = "duration", value_parser = parse_duration)]
+    max_age: Option<Duration>,
+    /// Timestamp from which the age of build cache records is measured.
+    #[arg(long, value_name = "timestamp", value_enum, default_value_t)]
+    age_from: BuildCacheAgeFrom,
+    /// Abort the run without removing anything if more than this many build cache records would
+    /// be removed, or more than this percentage of all records if suffixed with %.
+    #[serde(deserialize_with = "deserialize_removal_limit")]
+    #[arg(long, value_name = "n|percent%", value_parser = parse_removal_limit)]
+    max_removals: Option<RemovalLimit>,
+}
+
+impl ContainersArgs {
+    fn config<'a>(
+        &'a self,
+        dry_run: bool,
+        activity: Option<&'a ActivityTracker>,
+        throttle: Option<&'a Throttle>,
+    ) -> ReapContainersConfig<'a> {
+        ReapContainersConfig {
+            dry_run,
+            min_age: self.min_age,
+            max_age: self.max_age,
+            age_from: self.age_from,
+            filters: &self.filters,
+            name_regexes: &self.name_regex,
+            name_globs: &self.name_glob,
+            excludes: &self.excludes,
+            match_expression: self.match_expression.as_ref(),
+            ttl_label: self.ttl_label.as_deref(),
+            default_ttl: self.default_ttl,
+            expiry_label: self.expiry_label.as_deref(),
+            reap_networks: self.reap_networks,
+            protected_networks: &self.protected_networks,
+            reap_volumes: self.reap_volumes,
+            stop_timeout: self.stop_timeout,
+            stop_signal: self.stop_signal.as_deref(),
+            idle: self
+                .idle_for
+                .zip(activity)
+                .map(|(idle_for, tracker)| IdleCriteria {
+                    idle_for,
+                    cpu_threshold: self.idle_cpu_threshold.unwrap_or(DEFAULT_CPU_THRESHOLD),
+                    network_threshold: self
+                        .idle_network_threshold
+                        .unwrap_or(DEFAULT_NETWORK_THRESHOLD),
+                    tracker,
+                }),
+            group_by_label: self.group_by_label.as_deref(),
+            keep_newest: self.keep_newest,
+            max_count: self.max_count,
+            protection_label: self
+                .protection_label
+                .as_ref()
+                .unwrap_or(&DEFAULT_PROTECTION_LABEL),
+            max_removals: self.max_removals,
+            throttle,
+        }
+    }
+}
+
+impl NetworksArgs {
+    fn config<'a>(
+        &'a self,
+        dry_run: bool,
+        throttle: Option<&'a Throttle>,
+    ) -> ReapNetworksConfig<'a> {
+        ReapNetworksConfig {
+            dry_run,
+            min_age: self.min_age,
+            max_age: self.max_age,
+            filters: &self.filters,
+            name_regexes: &self.name_regex,
+            name_globs: &self.name_glob,
+            excludes: &self.excludes,
+            match_expression: self.match_expression.as_ref(),
+            ttl_label: self.ttl_label.as_deref(),
+            default_ttl: self.default_ttl,
+            expiry_label: self.expiry_label.as_deref(),
+            protected_networks: &self.protected_networks,
+            unused_only: self.unused_only,
+            max_count: self.max_count,
+            protection_label: self
+                .protection_label
+                .as_ref()
+                .unwrap_or(&DEFAULT_PROTECTION_LABEL),
+            max_removals: self.max_removals,
+            throttle,
+        }
+    }
+}
+
+impl VolumesArgs {
+    fn config<'a>(
+        &'a self,
+        dry_run: bool,
+        throttle: Option<&'a Throttle>,
+    ) -> ReapVolumesConfig<'a> {
+        ReapVolumesConfig {
+            dry_run,
+            min_age: self.min_age,
+            max_age: self.max_age,
+            filters: &self.filters,
+            name_regexes: &self.name_regex,
+            name_globs: &self.name_glob,
+            excludes: &self.excludes,
+            match_expression: self.match_expression.as_ref(),
+            ttl_label: self.ttl_label.as_deref(),
+            default_ttl: self.default_ttl,
+            expiry_label: self.expiry_label.as_deref(),
+            unused_only: self.unused_only,
+            max_count: self.max_count,
+            protection_label: self
+                .protection_label
+                .as_ref()
+                .unwrap_or(&DEFAULT_PROTECTION_LABEL),
+            max_removals: self.max_removals,
+            throttle,
+        }
+    }
+}
+
+impl ImagesArgs {
+    fn config<'a>(&'a self, dry_run: bool, throttle: Option<&'a Throttle>) -> ReapImagesConfig<'a> {
+        ReapImagesConfig {
+            dry_run,
+            min_age: self.min_age,
+            max_age: self.max_age,
+            filters: &self.filters,
+            force: self.force,
+            protection_label: self
+                .protection_label
+                .as_ref()
+                .unwrap_or(&DEFAULT_PROTECTION_LABEL),
+            max_removals: self.max_removals,
+            throttle,
+        }
+    }
+}
+
+impl BuildCacheArgs {
+    fn config<'a>(
+        &'a self,
+        dry_run: bool,
+        throttle: Option<&'a Throttle>,
+    ) -> ReapBuildCacheConfig<'a> {
+        ReapBuildCacheConfig {
+            dry_run,
+            min_age: self.min_age,
+            max_age: self.max_age,
+            age_from: self.age_from,
+            max_removals: self.max_removals,
+            throttle,
+        }
+    }
 }
 
 fn parse_filter(value: &str) -> Result<Filter, anyhow::Error> {
@@ -115,6 +599,24 @@ fn parse_filter(value: &str) -> Result<Filter, anyhow::Error> {
     Ok(Filter::new(name, value))
 }
 
+fn parse_exclude(value: &str) -> Result<Filter, anyhow::Error> {
+    let filter = parse_filter(value)?;
+    if !Filter::CLIENT_SIDE_NAMES.contains(&filter.name()) {
+        anyhow::bail!(
+            "exclusion filters must be one of: {}",
+            Filter::CLIENT_SIDE_NAMES.join(", ")
+        );
+    }
+    Ok(filter)
+}
+
+fn parse_protection_label(value: &str) -> Result<ProtectionLabel, anyhow::Error> {
+    if value.is_empty() || value.starts_with('=') {
+        anyhow::bail!("protection labels must be in KEY=VALUE or KEY format");
+    }
+    Ok(ProtectionLabel::new(value))
+}
+
 fn parse_duration(value: &str) -> Result<Duration, anyhow::Error> {
     let sleep_ns = match go_parse_duration::parse_duration(value) {
         Ok(ns) if ns < 1 => {
@@ -127,24 +629,366 @@ fn parse_duration(value: &str) -> Result<Duration, anyhow::Error> {
     Ok(Duration::from_nanos(sleep_ns))
 }
 
+fn parse_rate(value: &str) -> Result<f64, anyhow::Error> {
+    let rate: f64 = value
+        .parse()
+        .with_context(|| format!("failed to parse rate: {}", value))?;
+    if !(rate.is_finite() && rate >= MIN_RATE) {
+        anyhow::bail!("must be a rate of at least one removal per day: {}", value);
+    }
+    Ok(rate)
+}
+
+fn parse_percent(value: &str) -> Result<f64, anyhow::Error> {
+    let percent: f64 = value
+        .parse()
+        .with_context(|| format!("failed to parse percentage: {}", value))?;
+    if !(0.0..=100.0).contains(&percent) {
+        anyhow::bail!("must be a percentage between 0 and 100: {}", value);
+    }
+    Ok(percent)
+}
+
+fn parse_removal_limit(value: &str) -> Result<RemovalLimit, anyhow::Error> {
+    match value.strip_suffix('%') {
+        Some(percent) => parse_percent(percent).map(RemovalLimit::Percent),
+        None => value
+            .parse()
+            .map(RemovalLimit::Count)
+            .with_context(|| format!("failed to parse removal limit: {}", value)),
+    }
+}
+
+fn deserialize_removal_limit<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<RemovalLimit>, D::Error> {
+    let value = String::deserialize(deserializer)?;
+    parse_removal_limit(&value)
+        .map(Some)
+        .map_err(serde::de::Error::custom)
+}
+
+fn deserialize_duration<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<Duration>, D::Error> {
+    let value = String::deserialize(deserializer)?;
+    parse_duration(&value)
+        .map(Some)
+        .map_err(serde::de::Error::custom)
+}
+
+fn deserialize_match_expression<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<MatchExpression>, D::Error> {
+    let value = String::deserialize(deserializer)?;
+    value.parse().map(Some).map_err(serde::de::Error::custom)
+}
+
+fn parse_name_regex(value: &str) -> Result<NamePattern, anyhow::Error> {
+    NamePattern::regex(value).map_err(|e| match e.syntax_error() {
+        Some(syntax_error) => anyhow::anyhow!("invalid regular expression:\n{}", syntax_error),
+        None => anyhow::anyhow!("invalid regular expression {}: {}", value, e),
+    })
+}
+
+fn parse_name_glob(value: &str) -> Result<NamePattern, anyhow::Error> {
+    NamePattern::glob(value).map_err(|e| anyhow::anyhow!("invalid glob pattern {}: {}", value, e))
+}
+
+fn deserialize_name_regexes<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Vec<NamePattern>, D::Error> {
+    Vec::<String>::deserialize(deserializer)?
+        .iter()
+        .map(|value| parse_name_regex(value).map_err(serde::de::Error::custom))
+        .collect()
+}
+
+fn deserialize_name_globs<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Vec<NamePattern>, D::Error> {
+    Vec::<String>::deserialize(deserializer)?
+        .iter()
+        .map(|value| parse_name_glob(value).map_err(serde::de::Error::custom))
+        .collect()
+}
+
+fn deserialize_excludes<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Vec<Filter>, D::Error> {
+    Vec::<String>::deserialize(deserializer)?
+        .iter()
+        .map(|value| parse_exclude(value).map_err(serde::de::Error::custom))
+        .collect()
+}
+
+fn deserialize_protection_label<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<ProtectionLabel>, D::Error> {
+    let value = String::deserialize(deserializer)?;
+    parse_protection_label(&value)
+        .map(Some)
+        .map_err(serde::de::Error::custom)
+}
+
+fn deserialize_filters<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Vec<Filter>, D::Error> {
+    Vec::<String>::deserialize(deserializer)?
+        .iter()
+        .map(|value| parse_filter(value).map_err(serde::de::Error::custom))
+        .collect()
+}
+
+impl Commands {
+    /// Maximum number of matching resources, if the subcommand supports and sets it.
+    fn max_count(&self) -> Option<usize> {
+        match self {
+            Commands::Containers(args) => args.max_count,
+            Commands::Networks(args) => args.max_count,
+            Commands::Volumes(args) => args.max_count,
+            Commands::Images(_) | Commands::BuildCache(_) => None,
+        }
+    }
+
+    fn match_expression(&self) -> Option<&MatchExpression> {
+        match self {
+            Commands::Containers(args) => args.match_expression.as_ref(),
+            Commands::Networks(args) => args.match_expression.as_ref(),
+            Commands::Volumes(args) => args.match_expression.as_ref(),
+            Commands::Images(_) | Commands::BuildCache(_) => None,
+        }
+    }
+
+    /// Options for removing the resources matched by the subcommand.
+    fn removal_options<'a>(&'a self, throttle: &'a Throttle) -> RemovalOptions<'a> {
+        let throttle = Some(throttle);
+        match self {
+            Commands::Containers(args) => args.config(false, None, throttle).removal_options(),
+            Commands::Networks(args) => args.config(false, throttle).removal_options(),
+            Commands::Volumes(args) => args.config(false, throttle).removal_options(),
+            Commands::Images(args) => args.config(false, throttle).removal_options(),
+            Commands::BuildCache(args) => args.config(false, throttle).removal_options(),
+        }
+    }
+
+    /// Runs a single reaping attempt for the subcommand. Container activity is tracked across
+    /// attempts in `activity`, and removals are limited by `throttle`.
+    async fn reap(
+        &self,
+        docker: &Docker,
+        dry_run: bool,
+        activity: &ActivityTracker,
+        throttle: &Throttle,
+    ) -> Result<Vec<Resource>, ReapError> {
+        let throttle = Some(throttle);
+        match self {
+            Commands::Containers(args) => {
+                reap_containers(docker, &args.config(dry_run, Some(activity), throttle)).await
+            }
+            Commands::Networks(args) => {
+                reap_networks(docker, &args.config(dry_run, throttle)).await
+            }
+            Commands::Volumes(args) => reap_volumes(docker, &args.config(dry_run, throttle)).await,
+            Commands::Images(args) => reap_images(docker, &args.config(dry_run, throttle)).await,
+            Commands::BuildCache(args) => {
+                reap_build_cache(docker, &args.config(dry_run, throttle)).await
+            }
+        }
+    }
+}
+
+fn connect() -> Result<Docker, bollard::errors::Error> {
+    if env::var("DOCKER_CERT_PATH").is_ok() {
+        debug!("Environment variable DOCKER_CERT_PATH set. Connecting via TLS");
+        Docker::connect_with_ssl_defaults()
+    } else if env::var("DOCKER_HOST").is_ok() {
+        debug!(
+            "Environment variable DOCKER_HOST set, but not DOCKER_CERT_PATH. Connecting via HTTP"
+        );
+        Docker::connect_with_http_defaults()
+    } else {
+        debug!("Environment variable DOCKER_HOST not set, connecting to local machine");
+        Docker::connect_with_local_defaults()
+    }
+}
+
 #[tokio::main]
-async fn main() -> Result<(), anyhow::Error> {
-    tracing_subscriber::fmt::init();
+async fn main() -> Result<ExitCode, anyhow::Error> {
+    // Logs go to stderr so that stdout only contains the reports.
+    tracing_subscriber::fmt()
+        .with_writer(std::io::stderr)
+        .init();
 
     let global_args = Cli::parse();
-    let docker = {
-        if env::var("DOCKER_CERT_PATH").is_ok() {
-            debug!("Environment variable DOCKER_CERT_PATH set. Connecting via TLS");
-            Docker::connect_with_ssl_defaults()?
-        } else if env::var("DOCKER_HOST").is_ok() {
-            debug!(
-                "Environment variable DOCKER_HOST set, but not DOCKER_CERT_PATH. Connecting via HTTP"
-            );
-            Docker::connect_with_http_defaults()?
-        } else {
-            debug!("Environment variable DOCKER_HOST not set, connecting to local machine");
-            Docker::connect_with_local_defaults()?
+    let command = match (global_args.command, &global_args.config) {
+        (Some(command), None) => Some(command),
+        (None, Some(_)) => None,
+        (Some(_), Some(_)) => Cli::command()
+            .error(
+                clap::error::ErrorKind::ArgumentConflict,
+                "--config cannot be used with a subcommand",
+            )
+            .exit(),
+        (None, None) => Cli::command()
+            .error(
+                clap::error::ErrorKind::MissingSubcommand,
+                "either a subcommand or --config is required",
+            )
+            .exit(),
+    };
+    if let Some(Commands::Containers(ref args)) = command {
+        // Watch mode reaps containers individually, without knowing about their idle time or
+        // other containers in their group
+        if global_args.watch && args.idle_for.is_some() {
+            Cli::command()
+                .error(
+                    clap::error::ErrorKind::ArgumentConflict,
+                    "--idle-for cannot be used with --watch",
+                )
+                .exit()
+        }
+        if global_args.watch && args.group_by_label.is_some() {
+            Cli::command()
+                .error(
+                    clap::error::ErrorKind::ArgumentConflict,
+                    "--group-by-label cannot be used with --watch",
+                )
+                .exit()
+        }
+        if args.idle_for.is_some() && global_args.every.is_none() {
+            Cli::command()
+                .error(
+                    clap::error::ErrorKind::MissingRequiredArgument,
+                    "--idle-for requires --every",
+                )
+                .exit()
+        }
+    }
+    if (global_args.watch || global_args.config.is_some())
+        && (global_args.when_disk_usage_above.is_some() || global_args.free_space_below.is_some())
+    {
+        Cli::command()
+            .error(
+                clap::error::ErrorKind::ArgumentConflict,
+                "--when-disk-usage-above and --free-space-below cannot be used with --watch or \
+                 --config",
+            )
+            .exit()
+    }
+    if global_args.watch
+        && command
+            .as_ref()
+            .is_some_and(|command| command.max_count().is_some())
+    {
+        Cli::command()
+            .error(
+                clap::error::ErrorKind::ArgumentConflict,
+                "--max-count cannot be used with --watch",
+            )
+            .exit()
+    }
+    // Expressions may depend on the age of resources, which is only known at the time of each run
+    if global_args.watch
+        && command
+            .as_ref()
+            .is_some_and(|command| command.match_expression().is_some())
+    {
+        Cli::command()
+            .error(
+                clap::error::ErrorKind::ArgumentConflict,
+                "--match cannot be used with --watch",
+            )
+            .exit()
+    }
+    let config = global_args
+        .config
+        .as_deref()
+        .map(|path| Config::load(path, global_args.every))
+        .transpose()?;
+    let docker = connect()?;
+    if let Some(addr) = global_args.metrics_listen {
+        monitoring::install(addr).context("failed to start metrics endpoint")?;
+        info!("Serving metrics on http://{}/metrics", addr);
+    }
+    let mut reporter = Reporter::new(global_args.output, global_args.in_progress_is_failure);
+    // Removals are throttled across all runs, rules, and resource types
+    let throttle = Throttle::new(
+        global_args
+            .concurrency
+            .map(|concurrency| concurrency as usize),
+        global_args.rate,
+    );
+    if global_args.dry_run {
+        warn!("Dry run: no resources will be removed");
+    }
+    if let Some(config) = config {
+        info!("Running {} rules from config file", config.rules.len());
+        config::run(
+            &docker,
+            &config.rules,
+            global_args.every,
+            global_args.dry_run,
+            &throttle,
+            &mut reporter,
+        )
+        .await;
+        return Ok(reporter.outcome().exit_code());
+    }
+    let command = command.expect("subcommand is present without --config");
+    if global_args.watch {
+        let resync_interval = global_args.every.unwrap_or(DEFAULT_RESYNC_INTERVAL);
+        info!(
+            "Watching for events, resyncing every {} seconds",
+            resync_interval.as_secs()
+        );
+        let dry_run = global_args.dry_run;
+        match command {
+            Commands::Containers(ref args) => {
+                watch(
+                    &docker,
+                    &args.config(dry_run, None, Some(&throttle)),
+                    resync_interval,
+                    &mut reporter,
+                )
+                .await
+            }
+            Commands::Networks(ref args) => {
+                watch(
+                    &docker,
+                    &args.config(dry_run, Some(&throttle)),
+                    resync_interval,
+                    &mut reporter,
+                )
+                .await
+            }
+            Commands::Volumes(ref args) => {
+                watch(
+                    &docker,
+                    &args.config(dry_run, Some(&throttle)),
+                    resync_interval,
+                    &mut reporter,
+                )
+                .await
+            }
+            Commands::Images(_) | Commands::BuildCache(_) => {
+                anyhow::bail!("--watch is only supported for containers, networks, and volumes")
+            }
         }
+        return Ok(reporter.outcome().exit_code());
+    }
+
+    let threshold = match (
+        global_args.when_disk_usage_above,
+        global_args.free_space_below,
+    ) {
+        (Some(percent), _) => Some(DiskThreshold::UsageAbove(percent)),
+        (None, Some(bytes)) => Some(DiskThreshold::FreeSpaceBelow(bytes)),
+        (None, None) => None,
+    };
+    let disk_gate = match threshold {
+        Some(threshold) => Some(DiskGate::new(&docker, threshold).await?),
+        None => None,
     };
 
     if let Some(duration) = global_args.every {
@@ -153,68 +997,35 @@ async fn main() -> Result<(), anyhow::Error> {
         info!("Reaping resources once");
     }
 
+    // Container activity is sampled once per run, so it is tracked across runs
+    let activity = ActivityTracker::default();
     loop {
         info!("Starting new run");
-        if global_args.dry_run {
-            warn!("Dry run: no resources will be removed");
-        }
-        let result = match global_args.command {
-            Commands::Containers(ref args) => {
-                let config = ReapContainersConfig {
-                    dry_run: global_args.dry_run,
-                    min_age: args.min_age,
-                    max_age: args.max_age,
-                    filters: &args.filters,
-                    reap_networks: args.reap_networks,
-                };
-                reap_containers(&docker, &config).await
+        let started = Instant::now();
+        let result = match disk_gate {
+            Some(ref gate) => {
+                reap_until_relieved(
+                    &docker,
+                    &command,
+                    global_args.dry_run,
+                    &activity,
+                    &throttle,
+                    gate,
+                )
+                .await
             }
-            Commands::Networks(ref args) => {
-                let config = ReapNetworksConfig {
-                    dry_run: global_args.dry_run,
-                    min_age: args.min_age,
-                    max_age: args.max_age,
-                    filters: &args.filters,
-                };
-                reap_networks(&docker, &config).await
-            }
-            Commands::Volumes(ref args) => {
-                let config = ReapVolumesConfig {
-                    dry_run: global_args.dry_run,
-                    min_age: args.min_age,
-                    max_age: args.max_age,
-                    filters: &args.filters,
-                };
-                reap_volumes(&docker, &config).await
+            None => {
+                command
+                    .reap(&docker, global_args.dry_run, &activity, &throttle)
+                    .await
             }
         };
-        match result {
-            Ok(removed_resources) => {
-                info!("Found {} matching resources", removed_resources.len());
-                if !removed_resources.is_empty() {
-                    use tabled::{
-                        Table,
-                        settings::{Style, Width, object::Columns},
-                    };
-                    let mut table = Table::new(removed_resources);
-                    info!(
-                        "\n{}",
-                        table
-                            .with(Style::sharp())
-                            .modify(Columns::last(), Width::wrap(80))
-                            .to_string()
-                    );
-                }
-            }
-            Err(e) => {
-                error!("{}", e.to_string());
-            }
-        }
+        reporter.report(result, None, started.elapsed());
         if let Some(duration) = global_args.every {
             debug!("Sleeping for {:?}", global_args.every);
             sleep(duration).await;
         } else {
-            break Ok(());
+            break Ok(reporter.outcome().exit_code());
         }
     }
 }
@@ -0,0 +1,97 @@
+use metrics::{counter, describe_counter, describe_gauge, describe_histogram, gauge, histogram};
+use metrics_exporter_prometheus::{Matcher, PrometheusBuilder};
+use std::net::SocketAddr;
+use tokio::time::Duration;
+
+use crate::output::Outcome;
+use crate::reaper::Resource;
+
+const RESOURCES_TOTAL: &str = "docker_reaper_resources_total";
+const SKIPPED_TOTAL: &str = "docker_reaper_skipped_total";
+const RUNS_TOTAL: &str = "docker_reaper_runs_total";
+const RUN_DURATION: &str = "docker_reaper_run_duration_seconds";
+const LAST_SUCCESS: &str = "docker_reaper_last_success_timestamp_seconds";
+const ELIGIBLE_RESOURCES: &str = "docker_reaper_eligible_resources";
+
+/// Histogram buckets for run durations, in seconds.
+const RUN_DURATION_BUCKETS: &[f64] = &[0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0, 300.0];
+
+/// Starts serving Prometheus metrics over HTTP on the given address.
+pub(crate) fn install(addr: SocketAddr) -> Result<(), anyhow::Error> {
+    PrometheusBuilder::new()
+        .with_http_listener(addr)
+        .set_buckets_for_metric(
+            Matcher::Full(RUN_DURATION.to_string()),
+            RUN_DURATION_BUCKETS,
+        )?
+        .install()?;
+    describe_counter!(
+        RESOURCES_TOTAL,
+        "Matching resources by resource type and removal status."
+    );
+    describe_counter!(
+        SKIPPED_TOTAL,
+        "Resources skipped because of malformed timestamps or labels."
+    );
+    describe_counter!(RUNS_TOTAL, "Completed runs by outcome.");
+    describe_histogram!(
+        RUN_DURATION,
+        metrics::Unit::Seconds,
+        "Duration of each run."
+    );
+    describe_gauge!(
+        LAST_SUCCESS,
+        metrics::Unit::Seconds,
+        "UNIX timestamp of the last run in which no removal failed."
+    );
+    describe_gauge!(
+        ELIGIBLE_RESOURCES,
+        "Resources found eligible for removal in the last run."
+    );
+    Ok(())
+}
+
+/// Records the result of a single run. Does nothing unless [`install`] has been called.
+pub(crate) fn record_run(
+    resources: Option<&[Resource]>,
+    outcome: Outcome,
+    rule: Option<&str>,
+    duration: Duration,
+) {
+    let rule = rule.unwrap_or_default().to_string();
+    let outcome_key = match outcome {
+        Outcome::NothingMatched => "nothing_matched",
+        Outcome::Success => "success",
+        Outcome::PartialFailure => "partial_failure",
+        Outcome::Fatal => "fatal",
+    };
+    counter!(RUNS_TOTAL, "rule" => rule.clone(), "outcome" => outcome_key).increment(1);
+    histogram!(RUN_DURATION, "rule" => rule.clone()).record(duration.as_secs_f64());
+    if matches!(outcome, Outcome::NothingMatched | Outcome::Success) {
+        gauge!(LAST_SUCCESS, "rule" => rule.clone()).set(chrono::Utc::now().timestamp() as f64);
+    }
+    let Some(resources) = resources else {
+        return;
+    };
+    // Protected and in-use resources are reported, but were never candidates for removal
+    let eligible = resources
+        .iter()
+        .filter(|resource| !resource.status.is_skipped())
+        .count();
+    gauge!(ELIGIBLE_RESOURCES, "rule" => rule.clone()).set(eligible as f64);
+    for resource in resources {
+        counter!(
+            RESOURCES_TOTAL,
+            "rule" => rule.clone(),
+            "resource_type" => resource.resource_type.key(),
+            "status" => resource.status.key(),
+        )
+        .increment(1);
+    }
+}
+
+/// Records that a resource was skipped for the given reason. Does nothing unless [`install`] has
+/// been called.
+pub(crate) fn record_skip(kind: &str, reason: &'static str) {
+    counter!(SKIPPED_TOTAL, "kind" => kind.to_string(), "reason" => reason).increment(1);
+}
@@ -0,0 +1,221 @@
+use clap::ValueEnum;
+use serde::Serialize;
+use std::io::{self, Write};
+use std::process::ExitCode;
+use tokio::time::Duration;
+use tracing::{error, info, warn};
+
+use crate::monitoring;
+use crate::reaper::{ReapError, RemovalStatus, Resource};
+
+/// Format of the report written to stdout after each run.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub(crate) enum OutputFormat {
+    /// Human-readable table.
+    #[default]
+    Table,
+    /// A JSON array of resources per run.
+    Json,
+    /// One JSON object per resource and line.
+    Ndjson,
+    /// CSV rows with a single header row.
+    Csv,
+}
+
+/// Overall outcome of one or more runs, ordered by increasing severity.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum Outcome {
+    /// No resources matched.
+    NothingMatched,
+    /// All matching resources were removed (or found eligible for removal, in dry-run mode).
+    Success,
+    /// Removal of at least one matching resource failed.
+    PartialFailure,
+    /// At least one run failed entirely.
+    Fatal,
+}
+
+impl Outcome {
+    /// Process exit code for the outcome. Code 2 is skipped as it indicates usage errors.
+    pub(crate) fn exit_code(self) -> ExitCode {
+        match self {
+            Self::Success => ExitCode::SUCCESS,
+            Self::Fatal => ExitCode::from(1),
+            Self::PartialFailure => ExitCode::from(3),
+            Self::NothingMatched => ExitCode::from(4),
+        }
+    }
+}
+
+/// A single resource as serialized in machine-readable reports.
+#[derive(Debug, Serialize)]
+struct Record<'a> {
+    /// Name of the config file rule which matched the resource, if any.
+    rule: Option<&'a str>,
+    #[serde(rename = "type")]
+    resource_type: &'static str,
+    id: &'a str,
+    name: &'a str,
+    size: Option<u64>,
+    age_seconds: Option<u64>,
+    status: &'static str,
+    reason: Option<&'static str>,
+    error: Option<String>,
+}
+
+impl<'a> Record<'a> {
+    fn new(resource: &'a Resource, rule: Option<&'a str>) -> Self {
+        let error = match &resource.status {
+            RemovalStatus::Error(e) => Some(e.to_string()),
+            _ => None,
+        };
+        Self {
+            rule,
+            resource_type: resource.resource_type.key(),
+            id: &resource.id,
+            name: &resource.name,
+            size: resource.size,
+            age_seconds: resource.age.map(|age| age.as_secs()),
+            status: resource.status.key(),
+            reason: resource.reason,
+            error,
+        }
+    }
+}
+
+/// Writes the report of each run to stdout in the configured format, keeping track of the overall
+/// outcome.
+#[derive(Debug)]
+pub(crate) struct Reporter {
+    format: OutputFormat,
+    /// Whether resources whose removal was already in progress count as failures.
+    in_progress_is_failure: bool,
+    /// Whether the CSV header row has already been written.
+    wrote_header: bool,
+    outcome: Outcome,
+}
+
+impl Reporter {
+    pub(crate) fn new(format: OutputFormat, in_progress_is_failure: bool) -> Self {
+        Self {
+            format,
+            in_progress_is_failure,
+            wrote_header: false,
+            outcome: Outcome::NothingMatched,
+        }
+    }
+
+    /// The most severe outcome of all runs reported so far.
+    pub(crate) fn outcome(&self) -> Outcome {
+        self.outcome
+    }
+
+    /// Determines the outcome of a single successful run.
+    pub(crate) fn run_outcome(&self, resources: &[Resource]) -> Outcome {
+        // Skipped resources are reported, but do not count as matches
+        if resources
+            .iter()
+            .all(|resource| resource.status.is_skipped())
+        {
+            return Outcome::NothingMatched;
+        }
+        let failed = resources.iter().any(|resource| match resource.status {
+            RemovalStatus::Eligible
+            | RemovalStatus::Protected
+            | RemovalStatus::InUse
+            | RemovalStatus::Success => false,
+            RemovalStatus::InProgress => self.in_progress_is_failure,
+            RemovalStatus::Error(_) => true,
+        });
+        if failed {
+            Outcome::PartialFailure
+        } else {
+            Outcome::Success
+        }
+    }
+
+    /// Reports the outcome of a single run, optionally attributed to a config file rule.
+    pub(crate) fn report(
+        &mut self,
+        result: Result<Vec<Resource>, ReapError>,
+        rule: Option<&str>,
+        duration: Duration,
+    ) {
+        let outcome = match result {
+            Ok(ref removed_resources) => self.run_outcome(removed_resources),
+            Err(_) => Outcome::Fatal,
+        };
+        monitoring::record_run(result.as_deref().ok(), outcome, rule, duration);
+        self.outcome = self.outcome.max(outcome);
+        match result {
+            Ok(removed_resources) => {
+                info!("Found {} matching resources", removed_resources.len());
+                if let Err(e) = self.write(&mut io::stdout().lock(), &removed_resources, rule) {
+                    warn!("Failed to write report: {}", e);
+                }
+            }
+            Err(e) => {
+                error!("{}", e.to_string());
+                if let ReapError::TooManyRemovals { resources, .. } = &e
+                    && let Err(e) = self.write(&mut io::stdout().lock(), resources, rule)
+                {
+                    warn!("Failed to write report: {}", e);
+                }
+            }
+        }
+    }
+
+    /// Writes the report of a single run to the given writer.
+    pub(crate) fn write(
+        &mut self,
+        writer: &mut impl Write,
+        resources: &[Resource],
+        rule: Option<&str>,
+    ) -> Result<(), anyhow::Error> {
+        let records = resources.iter().map(|resource| Record::new(resource, rule));
+        match self.format {
+            OutputFormat::Table => {
+                if resources.is_empty() {
+                    return Ok(());
+                }
+                use tabled::{
+                    Table,
+                    settings::{Style, Width, object::Columns},
+                };
+                let mut table = Table::new(resources);
+                if let Some(rule) = rule {
+                    writeln!(writer, "Rule: {}", rule)?;
+                }
+                writeln!(
+                    writer,
+                    "{}",
+                    table
+                        .with(Style::sharp())
+                        .modify(Columns::last(), Width::wrap(80))
+                )?;
+            }
+            OutputFormat::Json => {
+                serde_json::to_writer(&mut *writer, &records.collect::<Vec<_>>())?;
+                writeln!(writer)?;
+            }
+            OutputFormat::Ndjson => {
+                for record in records {
+                    serde_json::to_writer(&mut *writer, &record)?;
+                    writeln!(writer)?;
+                }
+            }
+            OutputFormat::Csv => {
+                let mut csv_writer = csv::WriterBuilder::new()
+                    .has_headers(!self.wrote_header)
+                    .from_writer(&mut *writer);
+                for record in records {
+                    csv_writer.serialize(record)?;
+                    self.wrote_header = true;
+                }
+                csv_writer.flush()?;
+            }
+        }
+        writer.flush()?;
+        Ok(())
+    }
+}
@@ -1,16 +1,29 @@
 use bollard::Docker;
-use bollard::container::{ListContainersOptions, RemoveContainerOptions};
-use bollard::network::ListNetworksOptions;
-use bollard::service::VolumeListResponse;
-use bollard::volume::ListVolumesOptions;
+use bollard::query_parameters::{
+    DataUsageOptions, InspectContainerOptions, InspectNetworkOptions, ListContainersOptions,
+    ListImagesOptions, ListNetworksOptions, ListVolumesOptions, PruneBuildOptions,
+    RemoveContainerOptions, RemoveImageOptions, RemoveVolumeOptions, StopContainerOptions,
+};
+use bollard::service::{
+    BuildCache, ContainerStateStatusEnum, ContainerSummary, ImageSummary, MountPointTypeEnum,
+    Network, Volume, VolumeListResponse,
+};
+use chrono::{DateTime, TimeDelta, Utc};
+use clap::ValueEnum;
+use regex_automata::meta::{BuildError, Regex};
+use serde::Deserialize;
+use std::borrow::Cow;
 use std::collections::{HashMap, HashSet};
 use std::fmt;
-use std::time::{SystemTime, UNIX_EPOCH};
 use tabled::Tabled;
 use thiserror::Error;
 use tokio::time::Duration;
 use tracing::{debug, warn};
 
+use crate::activity::IdleCriteria;
+use crate::expression::MatchExpression;
+use crate::throttle::Throttle;
+
 #[derive(Debug)]
 pub(crate) struct ReapContainersConfig<'a> {
     /// Return results without actually removing containers or networks.
@@ -19,10 +32,61 @@ pub(crate) struct ReapContainersConfig<'a> {
     pub(crate) min_age: Option<Duration>,
     /// Only containers younger than this duration will be eligible for reaping.
     pub(crate) max_age: Option<Duration>,
+    /// Which timestamp the age of a container is measured from.
+    pub(crate) age_from: ContainerAgeFrom,
     /// Additional Docker Engine-supported [container filters](https://docs.docker.com/engine/reference/commandline/ps/#filter).
     pub(crate) filters: &'a Vec<Filter>,
+    /// If set, only containers with a name matching any of these regular expressions (or
+    /// `name_globs`) will be eligible for reaping.
+    pub(crate) name_regexes: &'a [NamePattern],
+    /// If set, only containers with a name matching any of these glob patterns (or `name_regexes`)
+    /// will be eligible for reaping.
+    pub(crate) name_globs: &'a [NamePattern],
+    /// Containers matching any of these filters are never reaped, even if they match
+    /// `filters`. Only [`Filter::CLIENT_SIDE_NAMES`] are supported.
+    pub(crate) excludes: &'a [Filter],
+    /// If set, only containers satisfying this expression will be eligible for reaping.
+    pub(crate) match_expression: Option<&'a MatchExpression>,
+    /// Label from which each container's time-to-live is read. If set, containers only become
+    /// eligible for reaping once they are older than their TTL.
+    pub(crate) ttl_label: Option<&'a str>,
+    /// TTL applied to containers without a `ttl_label` label. If unset, unlabeled containers are
+    /// not eligible for reaping.
+    pub(crate) default_ttl: Option<Duration>,
+    /// Label from which each container's expiration time is read, either as an RFC3339 timestamp or
+    /// as seconds since the UNIX epoch. If set, labeled containers only become eligible for reaping
+    /// once their expiration time has passed, regardless of `ttl_label`.
+    pub(crate) expiry_label: Option<&'a str>,
     /// Also attempt to remove the networks associated with reaped containers.
     pub(crate) reap_networks: bool,
+    /// Networks which are never removed, in addition to [`PREDEFINED_NETWORKS`].
+    pub(crate) protected_networks: &'a [String],
+    /// Containers with this label, and networks or volumes with it attached to reaped containers,
+    /// are never removed, even if they match all other criteria.
+    pub(crate) protection_label: &'a ProtectionLabel,
+    /// Also attempt to remove the volumes mounted by reaped containers.
+    pub(crate) reap_volumes: Option<ReapVolumesMode>,
+    /// If set, containers are stopped before being removed, and are killed if they have not
+    /// exited after this duration.
+    pub(crate) stop_timeout: Option<Duration>,
+    /// If set, containers are stopped with this signal (instead of their configured stop signal)
+    /// before being removed.
+    pub(crate) stop_signal: Option<&'a str>,
+    /// If set, only containers without meaningful activity for some time are eligible for reaping.
+    pub(crate) idle: Option<IdleCriteria<'a>>,
+    /// Label by which containers are grouped. Only containers with this label are eligible for
+    /// reaping, and only if they are not among the `keep_newest` newest containers with the same
+    /// label value.
+    pub(crate) group_by_label: Option<&'a str>,
+    /// Number of containers to keep in each group. Must be set together with `group_by_label`.
+    pub(crate) keep_newest: Option<usize>,
+    /// If more than this many containers match the filters, the oldest are reaped regardless of
+    /// their age until only this many remain.
+    pub(crate) max_count: Option<usize>,
+    /// If more containers than this would be removed, the run is aborted before removing any.
+    pub(crate) max_removals: Option<RemovalLimit>,
+    /// Limits on the concurrency and rate of removals, shared with other runs.
+    pub(crate) throttle: Option<&'a Throttle>,
 }
 
 #[derive(Debug)]
@@ -35,6 +99,41 @@ pub(crate) struct ReapNetworksConfig<'a> {
     pub(crate) max_age: Option<Duration>,
     /// Additional Docker Engine-supported [network filters](https://docs.docker.com/engine/reference/commandline/network_ls/#filter).
     pub(crate) filters: &'a Vec<Filter>,
+    /// If set, only networks with a name matching any of these regular expressions (or
+    /// `name_globs`) will be eligible for reaping.
+    pub(crate) name_regexes: &'a [NamePattern],
+    /// If set, only networks with a name matching any of these glob patterns (or `name_regexes`)
+    /// will be eligible for reaping.
+    pub(crate) name_globs: &'a [NamePattern],
+    /// Networks matching any of these filters are never reaped, even if they match
+    /// `filters`. Only [`Filter::CLIENT_SIDE_NAMES`] are supported.
+    pub(crate) excludes: &'a [Filter],
+    /// If set, only networks satisfying this expression will be eligible for reaping.
+    pub(crate) match_expression: Option<&'a MatchExpression>,
+    /// Label from which each network's time-to-live is read. If set, networks only become
+    /// eligible for reaping once they are older than their TTL.
+    pub(crate) ttl_label: Option<&'a str>,
+    /// TTL applied to networks without a `ttl_label` label. If unset, unlabeled networks are not
+    /// eligible for reaping.
+    pub(crate) default_ttl: Option<Duration>,
+    /// Label from which each network's expiration time is read, either as an RFC3339 timestamp or
+    /// as seconds since the UNIX epoch. If set, labeled networks only become eligible for reaping
+    /// once their expiration time has passed, regardless of `ttl_label`.
+    pub(crate) expiry_label: Option<&'a str>,
+    /// Networks which are never removed, in addition to [`PREDEFINED_NETWORKS`].
+    pub(crate) protected_networks: &'a [String],
+    /// Networks with this label are never removed, even if they match all other
+    /// criteria.
+    pub(crate) protection_label: &'a ProtectionLabel,
+    /// Only reap networks without any connected containers.
+    pub(crate) unused_only: bool,
+    /// If more than this many networks match the filters, the oldest are reaped regardless of
+    /// their age until only this many remain.
+    pub(crate) max_count: Option<usize>,
+    /// If more networks than this would be removed, the run is aborted before removing any.
+    pub(crate) max_removals: Option<RemovalLimit>,
+    /// Limits on the concurrency and rate of removals, shared with other runs.
+    pub(crate) throttle: Option<&'a Throttle>,
 }
 
 #[derive(Debug)]
@@ -47,12 +146,1023 @@ pub(crate) struct ReapVolumesConfig<'a> {
     pub(crate) max_age: Option<Duration>,
     /// Additional Docker Engine-supported [volume filters](https://docs.docker.com/engine/reference/commandline/volume_ls/#filter).
     pub(crate) filters: &'a Vec<Filter>,
+    /// If set, only volumes with a name matching any of these regular expressions (or
+    /// `name_globs`) will be eligible for reaping.
+    pub(crate) name_regexes: &'a [NamePattern],
+    /// If set, only volumes with a name matching any of these glob patterns (or `name_regexes`)
+    /// will be eligible for reaping.
+    pub(crate) name_globs: &'a [NamePattern],
+    /// Volumes matching any of these filters are never reaped, even if they match
+    /// `filters`. Only [`Filter::CLIENT_SIDE_NAMES`] are supported.
+    pub(crate) excludes: &'a [Filter],
+    /// If set, only volumes satisfying this expression will be eligible for reaping.
+    pub(crate) match_expression: Option<&'a MatchExpression>,
+    /// Label from which each volume's time-to-live is read. If set, volumes only become
+    /// eligible for reaping once they are older than their TTL.
+    pub(crate) ttl_label: Option<&'a str>,
+    /// TTL applied to volumes without a `ttl_label` label. If unset, unlabeled volumes are not
+    /// eligible for reaping.
+    pub(crate) default_ttl: Option<Duration>,
+    /// Label from which each volume's expiration time is read, either as an RFC3339 timestamp or
+    /// as seconds since the UNIX epoch. If set, labeled volumes only become eligible for reaping
+    /// once their expiration time has passed, regardless of `ttl_label`.
+    pub(crate) expiry_label: Option<&'a str>,
+    /// Volumes with this label are never removed, even if they match all other
+    /// criteria.
+    pub(crate) protection_label: &'a ProtectionLabel,
+    /// Only reap volumes which are not mounted by any container (running or not).
+    pub(crate) unused_only: bool,
+    /// If more than this many volumes match the filters, the oldest are reaped regardless of
+    /// their age until only this many remain.
+    pub(crate) max_count: Option<usize>,
+    /// If more volumes than this would be removed, the run is aborted before removing any.
+    pub(crate) max_removals: Option<RemovalLimit>,
+    /// Limits on the concurrency and rate of removals, shared with other runs.
+    pub(crate) throttle: Option<&'a Throttle>,
+}
+
+#[derive(Debug)]
+pub(crate) struct ReapImagesConfig<'a> {
+    /// Return results without actually removing images.
+    pub(crate) dry_run: bool,
+    /// Only images older than this duration will be eligible for reaping.
+    pub(crate) min_age: Option<Duration>,
+    /// Only images younger than this duration will be eligible for reaping.
+    pub(crate) max_age: Option<Duration>,
+    /// Additional Docker Engine-supported [image filters](https://docs.docker.com/engine/reference/commandline/images/#filter).
+    pub(crate) filters: &'a Vec<Filter>,
+    /// Remove images even if they are referenced by multiple tags.
+    pub(crate) force: bool,
+    /// Images with this label are never removed, even if they match all other
+    /// criteria.
+    pub(crate) protection_label: &'a ProtectionLabel,
+    /// If more images than this would be removed, the run is aborted before removing any.
+    pub(crate) max_removals: Option<RemovalLimit>,
+    /// Limits on the concurrency and rate of removals, shared with other runs.
+    pub(crate) throttle: Option<&'a Throttle>,
+}
+
+#[derive(Debug)]
+pub(crate) struct ReapBuildCacheConfig<'a> {
+    /// Return results without actually removing build cache records.
+    pub(crate) dry_run: bool,
+    /// Only build cache records older than this duration will be eligible for reaping.
+    pub(crate) min_age: Option<Duration>,
+    /// Only build cache records younger than this duration will be eligible for reaping.
+    pub(crate) max_age: Option<Duration>,
+    /// Which timestamp the age of a build cache record is measured from.
+    pub(crate) age_from: BuildCacheAgeFrom,
+    /// If more build cache records than this would be removed, the run is aborted before removing
+    /// any.
+    pub(crate) max_removals: Option<RemovalLimit>,
+    /// Limits on the concurrency and rate of removals, shared with other runs.
+    pub(crate) throttle: Option<&'a Throttle>,
+}
+
+impl<'a> ReapBuildCacheConfig<'a> {
+    /// Options for removing matching build cache records.
+    pub(crate) fn removal_options(&self) -> RemovalOptions<'a> {
+        RemovalOptions {
+            throttle: self.throttle,
+            ..Default::default()
+        }
+    }
+}
+
+/// The timestamp used to determine the age of a container.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum ContainerAgeFrom {
+    /// Time at which the container was created.
+    #[default]
+    Created,
+    /// Time at which the container was last started. Containers which were never started are not
+    /// reaped.
+    Started,
+    /// Time at which the container last exited. Containers which have not exited are not reaped.
+    Finished,
+}
+
+/// The timestamp used to determine the age of a build cache record.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum BuildCacheAgeFrom {
+    /// Time at which the record was created.
+    Created,
+    /// Time at which the record was last used, or its creation time if it was never used.
+    #[default]
+    LastUsed,
+}
+
+/// Networks created by Docker itself, which are never removed.
+pub(crate) const PREDEFINED_NETWORKS: [&str; 5] =
+    ["bridge", "host", "none", "ingress", "docker_gwbridge"];
+
+/// Label which protects resources from ever being reaped, given as `KEY=VALUE` or just `KEY` to
+/// match any value.
+#[derive(Clone, Debug)]
+pub(crate) struct ProtectionLabel {
+    key: Cow<'static, str>,
+    value: Option<Cow<'static, str>>,
+}
+
+/// Label protecting resources unless overridden with `--protection-label`.
+pub(crate) static DEFAULT_PROTECTION_LABEL: ProtectionLabel = ProtectionLabel {
+    key: Cow::Borrowed("docker-reaper.protect"),
+    value: Some(Cow::Borrowed("true")),
+};
+
+impl ProtectionLabel {
+    pub(crate) fn new(label: &str) -> Self {
+        match label.split_once('=') {
+            Some((key, value)) => Self {
+                key: Cow::Owned(key.to_string()),
+                value: Some(Cow::Owned(value.to_string())),
+            },
+            None => Self {
+                key: Cow::Owned(label.to_string()),
+                value: None,
+            },
+        }
+    }
+
+    /// Whether a resource with the given labels is protected.
+    fn protects(&self, labels: Option<&HashMap<String, String>>) -> bool {
+        let Some(value) = labels.and_then(|labels| labels.get(self.key.as_ref())) else {
+            return false;
+        };
+        self.value.as_ref().is_none_or(|expected| value == expected)
+    }
+
+    /// Docker Engine filter matching the resources protected by the label.
+    fn to_filter(&self) -> Filter {
+        match &self.value {
+            Some(value) => Filter::new("label", &format!("{}={}", self.key, value)),
+            None => Filter::new("label", &self.key),
+        }
+    }
+}
+
+impl fmt::Display for ProtectionLabel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.value {
+            Some(value) => write!(f, "{}={}", self.key, value),
+            None => write!(f, "{}", self.key),
+        }
+    }
+}
+
+/// Determines the initial status of a resource candidate, protecting those with the protection
+/// label.
+fn protection_status(resource_type: &ResourceType, name: &str, labeled: bool) -> RemovalStatus {
+    if labeled {
+        debug!("Skipped {} {}: protected by label", resource_type, name);
+        RemovalStatus::Protected
+    } else {
+        RemovalStatus::Eligible
+    }
+}
+
+/// Determines the initial status of a network candidate, protecting predefined networks, those
+/// explicitly configured to be protected, and those with the protection label.
+fn network_status(name: &str, labeled: bool, protected_networks: &[String]) -> RemovalStatus {
+    if is_protected_network(name, false, protected_networks) {
+        debug!("Skipped network {}: protected", name);
+        RemovalStatus::Protected
+    } else {
+        protection_status(&ResourceType::Network, name, labeled)
+    }
+}
+
+/// Whether the network is predefined, explicitly configured to be protected, or has the
+/// protection label.
+fn is_protected_network(name: &str, labeled: bool, protected_networks: &[String]) -> bool {
+    labeled || PREDEFINED_NETWORKS.contains(&name) || protected_networks.iter().any(|n| n == name)
+}
+
+/// Which volumes mounted by reaped containers are also reaped.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum ReapVolumesMode {
+    /// Only anonymous volumes, which are not referenced by name and cannot be reused.
+    Anonymous,
+    /// All mounted volumes, including named volumes.
+    All,
+}
+
+/// Whether a volume name was generated by Docker for an anonymous volume, i.e. consists of 64
+/// lowercase hexadecimal digits.
+fn is_anonymous_volume(name: &str) -> bool {
+    name.len() == 64
+        && name
+            .bytes()
+            .all(|b| b.is_ascii_digit() || (b'a'..=b'f').contains(&b))
+}
+
+/// Default value for config `filters` fields.
+static NO_FILTERS: Vec<Filter> = Vec::new();
+
+impl Default for ReapContainersConfig<'_> {
+    fn default() -> Self {
+        Self {
+            dry_run: false,
+            min_age: None,
+            max_age: None,
+            age_from: ContainerAgeFrom::Created,
+            filters: &NO_FILTERS,
+            name_regexes: &[],
+            name_globs: &[],
+            excludes: &[],
+            match_expression: None,
+            ttl_label: None,
+            default_ttl: None,
+            expiry_label: None,
+            reap_networks: false,
+            protected_networks: &[],
+            protection_label: &DEFAULT_PROTECTION_LABEL,
+            reap_volumes: None,
+            stop_timeout: None,
+            stop_signal: None,
+            idle: None,
+            group_by_label: None,
+            keep_newest: None,
+            max_count: None,
+            max_removals: None,
+            throttle: None,
+        }
+    }
+}
+
+impl Default for ReapNetworksConfig<'_> {
+    fn default() -> Self {
+        Self {
+            dry_run: false,
+            min_age: None,
+            max_age: None,
+            filters: &NO_FILTERS,
+            name_regexes: &[],
+            name_globs: &[],
+            excludes: &[],
+            match_expression: None,
+            ttl_label: None,
+            default_ttl: None,
+            expiry_label: None,
+            protected_networks: &[],
+            protection_label: &DEFAULT_PROTECTION_LABEL,
+            unused_only: false,
+            max_count: None,
+            max_removals: None,
+            throttle: None,
+        }
+    }
+}
+
+impl Default for ReapVolumesConfig<'_> {
+    fn default() -> Self {
+        Self {
+            dry_run: false,
+            min_age: None,
+            max_age: None,
+            filters: &NO_FILTERS,
+            name_regexes: &[],
+            name_globs: &[],
+            excludes: &[],
+            match_expression: None,
+            ttl_label: None,
+            default_ttl: None,
+            expiry_label: None,
+            protection_label: &DEFAULT_PROTECTION_LABEL,
+            unused_only: false,
+            max_count: None,
+            max_removals: None,
+            throttle: None,
+        }
+    }
+}
+
+impl Default for ReapImagesConfig<'_> {
+    fn default() -> Self {
+        Self {
+            dry_run: false,
+            min_age: None,
+            max_age: None,
+            filters: &NO_FILTERS,
+            force: false,
+            protection_label: &DEFAULT_PROTECTION_LABEL,
+            max_removals: None,
+            throttle: None,
+        }
+    }
+}
+
+impl<'a> ReapContainersConfig<'a> {
+    fn name_patterns(&self) -> [&[NamePattern]; 2] {
+        [self.name_regexes, self.name_globs]
+    }
+
+    fn age_criteria(&self) -> AgeCriteria<'_> {
+        AgeCriteria {
+            min_age: self.min_age,
+            max_age: self.max_age,
+            ttl_label: self.ttl_label,
+            default_ttl: self.default_ttl,
+            expiry_label: self.expiry_label,
+        }
+    }
+
+    /// Options for removing matching containers and their associated resources.
+    pub(crate) fn removal_options(&self) -> RemovalOptions<'a> {
+        RemovalOptions {
+            remove_container_volumes: self.reap_volumes.is_some(),
+            stop_timeout: self.stop_timeout,
+            stop_signal: self.stop_signal,
+            throttle: self.throttle,
+            ..Default::default()
+        }
+    }
+}
+
+impl<'a> ReapImagesConfig<'a> {
+    /// Options for removing matching images.
+    pub(crate) fn removal_options(&self) -> RemovalOptions<'a> {
+        RemovalOptions {
+            force_images: self.force,
+            throttle: self.throttle,
+            ..Default::default()
+        }
+    }
+}
+
+impl<'a> ReapNetworksConfig<'a> {
+    /// Options for removing matching networks.
+    pub(crate) fn removal_options(&self) -> RemovalOptions<'a> {
+        RemovalOptions {
+            throttle: self.throttle,
+            ..Default::default()
+        }
+    }
+
+    fn name_patterns(&self) -> [&[NamePattern]; 2] {
+        [self.name_regexes, self.name_globs]
+    }
+
+    fn age_criteria(&self) -> AgeCriteria<'_> {
+        AgeCriteria {
+            min_age: self.min_age,
+            max_age: self.max_age,
+            ttl_label: self.ttl_label,
+            default_ttl: self.default_ttl,
+            expiry_label: self.expiry_label,
+        }
+    }
+}
+
+impl<'a> ReapVolumesConfig<'a> {
+    /// Options for removing matching volumes.
+    pub(crate) fn removal_options(&self) -> RemovalOptions<'a> {
+        RemovalOptions {
+            throttle: self.throttle,
+            ..Default::default()
+        }
+    }
+
+    fn name_patterns(&self) -> [&[NamePattern]; 2] {
+        [self.name_regexes, self.name_globs]
+    }
+
+    fn age_criteria(&self) -> AgeCriteria<'_> {
+        AgeCriteria {
+            min_age: self.min_age,
+            max_age: self.max_age,
+            ttl_label: self.ttl_label,
+            default_ttl: self.default_ttl,
+            expiry_label: self.expiry_label,
+        }
+    }
+}
+
+/// Age-based eligibility criteria shared by all reapers.
+#[derive(Debug, Default)]
+struct AgeCriteria<'a> {
+    min_age: Option<Duration>,
+    max_age: Option<Duration>,
+    ttl_label: Option<&'a str>,
+    default_ttl: Option<Duration>,
+    expiry_label: Option<&'a str>,
+}
+
+impl AgeCriteria<'_> {
+    /// Whether any age-based criteria were specified. If not, resources of any age are eligible.
+    fn is_set(&self) -> bool {
+        self.min_age.is_some()
+            || self.max_age.is_some()
+            || self.ttl_label.is_some()
+            || self.expiry_label.is_some()
+    }
+
+    /// Checks whether a resource of the given age and labels satisfies these criteria. The reason
+    /// for skipping an ineligible resource is logged.
+    fn is_eligible(
+        &self,
+        kind: &str,
+        name: &str,
+        age: Duration,
+        labels: Option<&HashMap<String, String>>,
+    ) -> bool {
+        let within_age_range = age > self.min_age.unwrap_or(Duration::ZERO)
+            && age < self.max_age.unwrap_or(Duration::MAX);
+        if !within_age_range {
+            debug!("Skipped {} {}: age outside of specified range", kind, name);
+            return false;
+        }
+        if let Some(expiry_label) = self.expiry_label
+            && let Some(value) = labels.and_then(|labels| labels.get(expiry_label))
+        {
+            let Some(expiration_time) = parse_expiration_time(value) else {
+                warn!(
+                    "Skipped {} {}: failed to parse expiration label value \"{}\"",
+                    kind, name, value
+                );
+                crate::monitoring::record_skip(kind, "invalid_label");
+                return false;
+            };
+            if Utc::now() < expiration_time {
+                debug!("Skipped {} {}: expiration time not yet reached", kind, name);
+                return false;
+            }
+            return true;
+        }
+        let Some(ttl_label) = self.ttl_label else {
+            if self.expiry_label.is_some() {
+                debug!("Skipped {} {}: missing expiration label", kind, name);
+                return false;
+            }
+            return true;
+        };
+        let ttl = match labels.and_then(|labels| labels.get(ttl_label)) {
+            Some(value) => match crate::parse_duration(value) {
+                Ok(ttl) => ttl,
+                Err(_) => {
+                    warn!(
+                        "Skipped {} {}: failed to parse TTL label value \"{}\"",
+                        kind, name, value
+                    );
+                    crate::monitoring::record_skip(kind, "invalid_label");
+                    return false;
+                }
+            },
+            None => match self.default_ttl {
+                Some(ttl) => ttl,
+                None => {
+                    debug!("Skipped {} {}: missing TTL label", kind, name);
+                    return false;
+                }
+            },
+        };
+        if age <= ttl {
+            debug!("Skipped {} {}: TTL not yet expired", kind, name);
+            return false;
+        }
+        true
+    }
+
+    /// Returns the time at which a resource created at `created` with the given labels will begin
+    /// to satisfy these criteria, or `None` if that will never happen.
+    fn expiration_time(
+        &self,
+        created: DateTime<Utc>,
+        labels: Option<&HashMap<String, String>>,
+    ) -> Option<DateTime<Utc>> {
+        let after = |duration: Duration| {
+            TimeDelta::from_std(duration)
+                .ok()
+                .and_then(|delta| created.checked_add_signed(delta))
+        };
+        let label = |name: &str| labels.and_then(|labels| labels.get(name));
+        let mut expiration_time = after(self.min_age.unwrap_or(Duration::ZERO))?;
+        if let Some(expiry_label) = self.expiry_label
+            && let Some(value) = label(expiry_label)
+        {
+            expiration_time = expiration_time.max(parse_expiration_time(value)?);
+        } else if let Some(ttl_label) = self.ttl_label {
+            let ttl = match label(ttl_label) {
+                Some(value) => crate::parse_duration(value).ok()?,
+                None => self.default_ttl?,
+            };
+            expiration_time = expiration_time.max(after(ttl)?);
+        } else if self.expiry_label.is_some() {
+            return None;
+        }
+        if let Some(max_age) = self.max_age
+            && let Some(limit) = after(max_age)
+            && expiration_time >= limit
+        {
+            return None;
+        }
+        Some(expiration_time)
+    }
+}
+
+/// Kind of build cache records, as used in log messages and metrics like [`Listed::KIND`].
+const BUILD_CACHE_KIND: &str = "build-cache";
+
+/// Common properties of the resources returned by the Docker Engine's list endpoints.
+pub(crate) trait Listed {
+    /// Kind of resource, as used in log messages.
+    const KIND: &'static str;
+
+    /// Identifier used for the resource in log messages.
+    fn log_name(&self) -> &str;
+
+    /// Identifier which uniquely selects the resource in Docker Engine filters.
+    fn id(&self) -> Option<&str>;
+
+    /// Names of the resource, as matched by [`NamePattern`]s.
+    fn names(&self) -> Vec<&str>;
+
+    fn labels(&self) -> Option<&HashMap<String, String>>;
+
+    /// State of the resource, for resources which have one.
+    fn status(&self) -> Option<&str> {
+        None
+    }
+
+    /// Returns the creation time of the resource, or the reason it could not be determined.
+    fn created(&self) -> Result<DateTime<Utc>, &'static str>;
+
+    /// Returns the time elapsed since the resource was created, if known.
+    fn age(&self) -> Option<Duration> {
+        let created = self.created().ok()?;
+        Utc::now().signed_duration_since(created).to_std().ok()
+    }
+}
+
+/// Converts a signed UNIX timestamp, as returned for containers and images, to a creation time.
+fn created_from_unix_timestamp(secs: Option<i64>) -> Result<DateTime<Utc>, &'static str> {
+    let Some(secs) = secs else {
+        return Err("missing creation timestamp");
+    };
+    if secs < 0 {
+        return Err("negative creation timestamp");
+    }
+    DateTime::from_timestamp(secs, 0).ok_or("creation timestamp out of range")
+}
+
+/// Converts an RFC3339 timestamp, as returned for networks and volumes, to a creation time.
+fn created_from_rfc3339(timestamp: Option<&String>) -> Result<DateTime<Utc>, &'static str> {
+    let Some(timestamp) = timestamp else {
+        return Err("missing creation timestamp");
+    };
+    DateTime::parse_from_rfc3339(timestamp)
+        .map(|time| time.to_utc())
+        .map_err(|_| "failed to parse creation timestamp as RFC3339")
+}
+
+impl Listed for ContainerSummary {
+    const KIND: &'static str = "container";
+
+    fn log_name(&self) -> &str {
+        self.id.as_deref().unwrap_or("unknown ID")
+    }
+
+    fn id(&self) -> Option<&str> {
+        self.id.as_deref()
+    }
+
+    fn names(&self) -> Vec<&str> {
+        // Container names are listed with a leading slash
+        self.names
+            .iter()
+            .flatten()
+            .map(|name| name.trim_start_matches('/'))
+            .collect()
+    }
+
+    fn labels(&self) -> Option<&HashMap<String, String>> {
+        self.labels.as_ref()
+    }
+
+    fn status(&self) -> Option<&str> {
+        self.state.as_ref().map(|state| state.as_ref())
+    }
+
+    fn created(&self) -> Result<DateTime<Utc>, &'static str> {
+        created_from_unix_timestamp(self.created)
+    }
+}
+
+/// A listed container, along with the time from which its age is measured if this is not its
+/// creation time.
+struct ListedContainer {
+    summary: ContainerSummary,
+    since: Option<DateTime<Utc>>,
+}
+
+impl Listed for ListedContainer {
+    const KIND: &'static str = ContainerSummary::KIND;
+
+    fn log_name(&self) -> &str {
+        self.summary.log_name()
+    }
+
+    fn id(&self) -> Option<&str> {
+        self.summary.id()
+    }
+
+    fn names(&self) -> Vec<&str> {
+        self.summary.names()
+    }
+
+    fn labels(&self) -> Option<&HashMap<String, String>> {
+        self.summary.labels()
+    }
+
+    fn status(&self) -> Option<&str> {
+        self.summary.status()
+    }
+
+    fn created(&self) -> Result<DateTime<Utc>, &'static str> {
+        match self.since {
+            Some(since) => Ok(since),
+            None => self.summary.created(),
+        }
+    }
+}
+
+/// Lists all containers matching the given filters. Unless their age is measured from their
+/// creation time, each container is inspected to determine its start or finish time, and
+/// containers which have not yet started or finished are omitted.
+async fn list_containers(
+    docker: &Docker,
+    filters: &Vec<Filter>,
+    age_from: ContainerAgeFrom,
+) -> Result<Vec<ListedContainer>, ReapError> {
+    let containers = docker
+        .list_containers(Some(ListContainersOptions {
+            all: true,
+            filters: Some(filters.to_bollard_filters()),
+            ..Default::default()
+        }))
+        .await?;
+    if age_from == ContainerAgeFrom::Created {
+        return Ok(containers
+            .into_iter()
+            .map(|summary| ListedContainer {
+                summary,
+                since: None,
+            })
+            .collect());
+    }
+    // Start and finish times are only included when inspecting containers, not when listing them
+    let inspections = futures::future::join_all(containers.into_iter().map(|summary| async {
+        let result = match summary.id.as_deref() {
+            Some(id) => Some(
+                docker
+                    .inspect_container(id, None::<InspectContainerOptions>)
+                    .await,
+            ),
+            None => None,
+        };
+        (summary, result)
+    }))
+    .await;
+    let mut listed_containers = Vec::new();
+    for (summary, result) in inspections {
+        let name = summary.log_name();
+        let container = match result {
+            Some(Ok(container)) => container,
+            // Containers which have been removed in the meantime no longer need to be reaped
+            Some(Err(bollard::errors::Error::DockerResponseServerError {
+                status_code: 404,
+                ..
+            })) => {
+                debug!("Skipped container {}: no longer exists", name);
+                continue;
+            }
+            Some(Err(e)) => return Err(e.into()),
+            None => {
+                warn!("Skipped container (unknown ID): missing ID value");
+                continue;
+            }
+        };
+        let state = container.state.unwrap_or_default();
+        let (timestamp, kind) = match age_from {
+            ContainerAgeFrom::Created => (container.created, "creation"),
+            ContainerAgeFrom::Started => {
+                if state.status == Some(ContainerStateStatusEnum::CREATED) {
+                    debug!("Skipped container {}: not yet started", name);
+                    continue;
+                }
+                (state.started_at, "start")
+            }
+            ContainerAgeFrom::Finished => {
+                if !matches!(
+                    state.status,
+                    Some(ContainerStateStatusEnum::EXITED | ContainerStateStatusEnum::DEAD)
+                ) {
+                    debug!("Skipped container {}: not yet exited", name);
+                    continue;
+                }
+                (state.finished_at, "finish")
+            }
+        };
+        let Some(since) = timestamp
+            .and_then(|timestamp| DateTime::parse_from_rfc3339(&timestamp).ok())
+            .map(|since| since.to_utc())
+        else {
+            warn!(
+                "Skipped container {}: missing or invalid {} timestamp",
+                name, kind
+            );
+            crate::monitoring::record_skip(ContainerSummary::KIND, "invalid_timestamp");
+            continue;
+        };
+        listed_containers.push(ListedContainer {
+            summary,
+            since: Some(since),
+        });
+    }
+    Ok(listed_containers)
+}
+
+impl Listed for Network {
+    const KIND: &'static str = "network";
+
+    fn log_name(&self) -> &str {
+        self.name.as_deref().unwrap_or("unknown name")
+    }
+
+    fn id(&self) -> Option<&str> {
+        self.id.as_deref()
+    }
+
+    fn names(&self) -> Vec<&str> {
+        self.name.as_deref().into_iter().collect()
+    }
+
+    fn labels(&self) -> Option<&HashMap<String, String>> {
+        self.labels.as_ref()
+    }
+
+    fn created(&self) -> Result<DateTime<Utc>, &'static str> {
+        created_from_rfc3339(self.created.as_ref())
+    }
+}
+
+impl Listed for Volume {
+    const KIND: &'static str = "volume";
+
+    fn log_name(&self) -> &str {
+        &self.name
+    }
+
+    fn id(&self) -> Option<&str> {
+        // Volumes have no ID separate from their (unique) name.
+        Some(&self.name)
+    }
+
+    fn names(&self) -> Vec<&str> {
+        vec![&self.name]
+    }
+
+    fn labels(&self) -> Option<&HashMap<String, String>> {
+        Some(&self.labels)
+    }
+
+    fn created(&self) -> Result<DateTime<Utc>, &'static str> {
+        created_from_rfc3339(self.created_at.as_ref())
+    }
+}
+
+impl Listed for ImageSummary {
+    const KIND: &'static str = "image";
+
+    fn log_name(&self) -> &str {
+        &self.id
+    }
+
+    fn id(&self) -> Option<&str> {
+        Some(&self.id)
+    }
+
+    fn names(&self) -> Vec<&str> {
+        self.repo_tags.iter().map(String::as_str).collect()
+    }
+
+    fn labels(&self) -> Option<&HashMap<String, String>> {
+        Some(&self.labels)
+    }
+
+    fn created(&self) -> Result<DateTime<Utc>, &'static str> {
+        created_from_unix_timestamp(Some(self.created))
+    }
+}
+
+/// Reduces the listed resources to only those satisfying the given age criteria (if any).
+fn retain_by_age<T: Listed>(resources: &mut Vec<T>, criteria: &AgeCriteria) {
+    if !criteria.is_set() {
+        return;
+    }
+    let now = Utc::now();
+    resources.retain(|resource| satisfies_age(resource, now, criteria));
+}
+
+/// Checks whether a listed resource satisfies the given age criteria at the given time. The reason
+/// for skipping an ineligible resource is logged.
+fn satisfies_age<T: Listed>(resource: &T, now: DateTime<Utc>, criteria: &AgeCriteria) -> bool {
+    let name = resource.log_name();
+    let creation_time = match resource.created() {
+        Ok(creation_time) => creation_time,
+        Err(reason) => {
+            warn!("Skipped {} {}: {}", T::KIND, name, reason);
+            crate::monitoring::record_skip(T::KIND, "invalid_timestamp");
+            return false;
+        }
+    };
+    let Ok(age) = now.signed_duration_since(creation_time).to_std() else {
+        warn!(
+            "Skipped {} {}: creation timestamp after system time",
+            T::KIND,
+            name
+        );
+        crate::monitoring::record_skip(T::KIND, "invalid_timestamp");
+        return false;
+    };
+    criteria.is_eligible(T::KIND, name, age, resource.labels())
+}
+
+/// Reason reported for resources which were evicted because more than `max_count` resources
+/// matched.
+const EVICTED_REASON: &str = "Evicted: over max count";
+
+/// Reduces the listed resources to only those satisfying the given age criteria (if any), like
+/// [`retain_by_age`], and additionally evicts (i.e. retains) the oldest other resources while more
+/// than `max_count` matching resources would remain. Returns the IDs of the evicted resources.
+///
+/// Every matching resource which would remain counts towards `max_count`: `kept` resources which
+/// were already excluded from reaping (e.g. the newest of their group), protected resources, and
+/// active resources (`is_active`), which do not become eligible by age. Protected and already
+/// excluded resources are never evicted, so if they alone exceed `max_count`, all other
+/// resources are evicted. Without any age criteria, only evicted resources are eligible, so that
+/// `max_count` on its own merely caps the number of resources rather than removing all of them.
+fn retain_by_age_or_count<T: Listed>(
+    resources: &mut Vec<T>,
+    criteria: &AgeCriteria,
+    max_count: Option<usize>,
+    kept: usize,
+    is_protected: impl Fn(&T) -> bool,
+    is_active: impl Fn(&T) -> bool,
+) -> HashSet<String> {
+    let Some(max_count) = max_count else {
+        retain_by_age(resources, criteria);
+        return HashSet::new();
+    };
+    let now = Utc::now();
+    let (mut eligible, remaining): (Vec<T>, Vec<T>) = resources.drain(..).partition(|resource| {
+        criteria.is_set() && !is_active(resource) && satisfies_age(resource, now, criteria)
+    });
+    let (protected, mut candidates): (Vec<T>, Vec<T>) =
+        remaining.into_iter().partition(&is_protected);
+    let count = kept
+        + protected.len()
+        + candidates.len()
+        + eligible
+            .iter()
+            .filter(|resource| is_protected(resource))
+            .count();
+    let mut evicted_ids = HashSet::new();
+    if count > max_count {
+        let excess = count - max_count;
+        if excess > candidates.len() {
+            warn!(
+                "Cannot evict enough {}s to stay within max count of {}: too many are protected",
+                T::KIND,
+                max_count
+            );
+        }
+        // Resources of unknown age count as the newest, so that they are evicted last
+        candidates.sort_by_key(|resource| resource.created().unwrap_or(DateTime::<Utc>::MAX_UTC));
+        for resource in candidates.drain(..excess.min(candidates.len())) {
+            debug!(
+                "Evicting {} {}: more than {} matching {}s",
+                T::KIND,
+                resource.log_name(),
+                max_count,
+                T::KIND
+            );
+            evicted_ids.extend(resource.id().map(String::from));
+            eligible.push(resource);
+        }
+    }
+    *resources = eligible;
+    evicted_ids
+}
+
+/// Reduces the listed resources to those with the given label, except for the `keep` newest
+/// resources with each label value.
+fn retain_all_but_newest<T: Listed>(resources: &mut Vec<T>, label: &str, keep: usize) {
+    let mut groups: HashMap<&str, Vec<(DateTime<Utc>, usize)>> = HashMap::new();
+    for (index, resource) in resources.iter().enumerate() {
+        let Some(value) = resource.labels().and_then(|labels| labels.get(label)) else {
+            debug!(
+                "Skipped {} {}: missing group label",
+                T::KIND,
+                resource.log_name()
+            );
+            continue;
+        };
+        // Resources of unknown age count as the newest, so that no more resources than intended
+        // are removed.
+        let created = resource.created().unwrap_or(DateTime::<Utc>::MAX_UTC);
+        groups.entry(value).or_default().push((created, index));
+    }
+    let mut eligible = vec![false; resources.len()];
+    for (value, mut members) in groups {
+        members.sort_unstable_by(|a, b| b.cmp(a));
+        debug!(
+            "Keeping {} of {} {}s with {}={}",
+            keep.min(members.len()),
+            members.len(),
+            T::KIND,
+            label,
+            value
+        );
+        for (_, index) in members.into_iter().skip(keep) {
+            eligible[index] = true;
+        }
+    }
+    let mut eligible = eligible.into_iter();
+    resources.retain(|_| eligible.next().unwrap_or(false));
+}
+
+/// The time at which a resource will become eligible for reaping.
+#[derive(Debug)]
+pub(crate) struct Expiration {
+    /// Identifier which uniquely selects the resource in Docker Engine filters: the ID of
+    /// containers and networks, or the name of volumes.
+    pub(crate) id: String,
+    pub(crate) time: DateTime<Utc>,
+}
+
+/// Determines when each of the listed resources will satisfy the given age criteria. Resources
+/// which will never do so are omitted.
+fn expirations<T: Listed>(resources: &[T], criteria: &AgeCriteria) -> Vec<Expiration> {
+    resources
+        .iter()
+        .filter_map(|resource| {
+            let created = resource.created().ok()?;
+            Some(Expiration {
+                id: resource.id()?.to_string(),
+                time: criteria.expiration_time(created, resource.labels())?,
+            })
+        })
+        .collect()
+}
+
+/// Parses an expiration time given either as an RFC3339 timestamp or as seconds since the UNIX
+/// epoch.
+fn parse_expiration_time(value: &str) -> Option<DateTime<Utc>> {
+    if let Ok(secs) = value.parse::<i64>() {
+        return DateTime::from_timestamp(secs, 0);
+    }
+    DateTime::parse_from_rfc3339(value)
+        .ok()
+        .map(|time| time.to_utc())
+}
+
+/// Time allowed for a stop request in addition to the stop timeout itself.
+const STOP_REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Options controlling how individual resources are removed.
+#[derive(Debug, Default)]
+pub(crate) struct RemovalOptions<'a> {
+    /// Untag and remove images even if they are referenced by multiple tags.
+    force_images: bool,
+    /// Remove anonymous volumes along with containers.
+    remove_container_volumes: bool,
+    /// Stop containers before removing them, killing them after this duration.
+    stop_timeout: Option<Duration>,
+    /// Stop containers before removing them, using this signal.
+    stop_signal: Option<&'a str>,
+    /// Limits on the concurrency and rate of removals.
+    throttle: Option<&'a Throttle>,
+}
+
+impl RemovalOptions<'_> {
+    /// Whether containers should be stopped gracefully before being removed.
+    fn stop_containers(&self) -> bool {
+        self.stop_timeout.is_some() || self.stop_signal.is_some()
+    }
 }
 
 #[derive(Debug)]
 pub(crate) enum RemovalStatus {
     /// Used in dry-run mode to indicate that a resource is eligible for removal.
     Eligible,
+    /// Resource matched, but is protected from removal.
+    Protected,
+    /// Resource matched, but is still in use and therefore cannot be removed.
+    InUse,
     /// Resource was successfully removed.
     Success,
     /// Removal was already in progress.
@@ -65,6 +1175,8 @@ impl fmt::Display for RemovalStatus {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Self::Eligible => write!(f, "Eligible for removal"),
+            Self::Protected => write!(f, "Skipped: protected"),
+            Self::InUse => write!(f, "Skipped: in use"),
             Self::Success => write!(f, "Removed"),
             &Self::InProgress => write!(f, "Removal in progress"),
             Self::Error(e) => write!(f, "Error: {}", e),
@@ -72,6 +1184,25 @@ impl fmt::Display for RemovalStatus {
     }
 }
 
+impl RemovalStatus {
+    /// Whether the resource matched, but was deliberately not removed.
+    pub(crate) fn is_skipped(&self) -> bool {
+        matches!(self, Self::Protected | Self::InUse)
+    }
+
+    /// Machine-readable identifier of the status, as used in reports and metrics.
+    pub(crate) fn key(&self) -> &'static str {
+        match self {
+            Self::Eligible => "eligible",
+            Self::Protected => "protected",
+            Self::InUse => "in_use",
+            Self::Success => "removed",
+            Self::InProgress => "in_progress",
+            Self::Error(_) => "error",
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 /// A Docker Engine filter (see <https://docs.docker.com/engine/reference/commandline/ps/#filter>)
 pub(crate) struct Filter {
@@ -88,29 +1219,165 @@ trait BollardConversionExt {
         Self: IntoIterator;
 }
 
-impl BollardConversionExt for Vec<Filter> {
-    fn to_bollard_filters(&self) -> HashMap<String, Vec<String>> {
-        self.iter().fold(HashMap::new(), |mut acc, f| {
-            acc.entry(f.name.clone()).or_default().push(f.value.clone());
-            acc
-        })
+impl BollardConversionExt for Vec<Filter> {
+    fn to_bollard_filters(&self) -> HashMap<String, Vec<String>> {
+        self.iter().fold(HashMap::new(), |mut acc, f| {
+            acc.entry(f.name.clone()).or_default().push(f.value.clone());
+            acc
+        })
+    }
+}
+
+impl Filter {
+    /// Filter names which can be evaluated client-side by [`Filter::matches`].
+    pub(crate) const CLIENT_SIDE_NAMES: &[&str] = &["id", "label", "name"];
+
+    pub(crate) fn new(name: &str, value: &str) -> Self {
+        Self {
+            name: String::from(name),
+            value: String::from(value),
+        }
+    }
+
+    pub(crate) fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Whether the listed resource matches the filter, mirroring the Docker Engine's semantics:
+    /// `id` matches a prefix of the ID, `label` matches either a label key or a `KEY=VALUE` pair,
+    /// and `name` matches part of any name. Filters with other names never match.
+    fn matches<T: Listed>(&self, resource: &T) -> bool {
+        match self.name.as_str() {
+            "id" => resource
+                .id()
+                .is_some_and(|id| id.starts_with(self.value.as_str())),
+            "label" => {
+                let labels = resource.labels();
+                match self.value.split_once('=') {
+                    Some((key, value)) => labels
+                        .and_then(|labels| labels.get(key))
+                        .is_some_and(|label_value| label_value == value),
+                    None => labels.is_some_and(|labels| labels.contains_key(&self.value)),
+                }
+            }
+            "name" => resource
+                .names()
+                .into_iter()
+                .any(|name| name.contains(self.value.as_str())),
+            _ => false,
+        }
+    }
+}
+
+/// A pattern which must match the entire name of a resource. Unlike the Docker Engine `name`
+/// filter, which matches substrings, patterns are evaluated client-side after listing resources.
+#[derive(Clone, Debug)]
+pub(crate) struct NamePattern(Regex);
+
+impl NamePattern {
+    /// Parses a regular expression, which is anchored at both ends.
+    pub(crate) fn regex(pattern: &str) -> Result<Self, Box<BuildError>> {
+        Ok(Self(Regex::new(&format!("^(?:{pattern})$"))?))
+    }
+
+    /// Parses a glob pattern, in which `*` matches any sequence of characters and `?` matches any
+    /// single character.
+    pub(crate) fn glob(pattern: &str) -> Result<Self, Box<BuildError>> {
+        let mut regex = String::new();
+        for c in pattern.chars() {
+            match c {
+                '*' => regex.push_str(".*"),
+                '?' => regex.push('.'),
+                c => regex_syntax::escape_into(c.encode_utf8(&mut [0; 4]), &mut regex),
+            }
+        }
+        Self::regex(&regex)
+    }
+
+    pub(crate) fn is_match(&self, name: &str) -> bool {
+        self.0.is_match(name)
+    }
+}
+
+/// Reduces the listed resources to only those with a name matching any of the given patterns (if
+/// any).
+fn retain_by_name<T: Listed>(resources: &mut Vec<T>, patterns: &[&[NamePattern]]) {
+    if patterns.iter().all(|patterns| patterns.is_empty()) {
+        return;
     }
+    resources.retain(|resource| {
+        let matches = resource.names().into_iter().any(|name| {
+            patterns
+                .iter()
+                .flat_map(|patterns| patterns.iter())
+                .any(|pattern| pattern.is_match(name))
+        });
+        if !matches {
+            debug!(
+                "Skipped {} {}: name does not match any pattern",
+                T::KIND,
+                resource.log_name()
+            );
+        }
+        matches
+    });
 }
 
-impl Filter {
-    pub(crate) fn new(name: &str, value: &str) -> Self {
-        Self {
-            name: String::from(name),
-            value: String::from(value),
+/// Removes all listed resources matching any of the given exclusion filters.
+fn retain_unexcluded<T: Listed>(resources: &mut Vec<T>, excludes: &[Filter]) {
+    resources.retain(|resource| {
+        let Some(exclude) = excludes.iter().find(|exclude| exclude.matches(resource)) else {
+            return true;
+        };
+        debug!(
+            "Skipped {} {}: excluded by filter {}={}",
+            T::KIND,
+            resource.log_name(),
+            exclude.name,
+            exclude.value
+        );
+        false
+    });
+}
+
+/// Reduces the listed resources to only those satisfying the given expression (if any).
+fn retain_by_expression<T: Listed>(resources: &mut Vec<T>, expression: Option<&MatchExpression>) {
+    let Some(expression) = expression else {
+        return;
+    };
+    resources.retain(|resource| {
+        let matches = expression.matches(resource);
+        if !matches {
+            debug!(
+                "Skipped {} {}: does not satisfy match expression",
+                T::KIND,
+                resource.log_name()
+            );
         }
-    }
+        matches
+    });
 }
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub(crate) enum ResourceType {
     Container,
     Network,
     Volume,
+    Image,
+    BuildCache,
+}
+
+impl ResourceType {
+    /// Machine-readable identifier of the resource type, as used in reports and metrics.
+    pub(crate) fn key(&self) -> &'static str {
+        match self {
+            Self::Container => "container",
+            Self::Network => "network",
+            Self::Volume => "volume",
+            Self::Image => "image",
+            Self::BuildCache => "build-cache",
+        }
+    }
 }
 
 impl fmt::Display for ResourceType {
@@ -125,6 +1392,12 @@ impl fmt::Display for ResourceType {
             Self::Volume => {
                 write!(f, "Volume")
             }
+            Self::Image => {
+                write!(f, "Image")
+            }
+            Self::BuildCache => {
+                write!(f, "Build Cache")
+            }
         }
     }
 }
@@ -137,7 +1410,55 @@ pub(crate) struct Resource {
     #[tabled(skip)]
     pub(crate) id: String,
     pub(crate) name: String,
+    /// Disk space used by the resource, if known.
+    #[tabled(display = "display_size")]
+    pub(crate) size: Option<u64>,
+    /// Time elapsed since the resource was created, if known.
+    #[tabled(display = "display_age")]
+    pub(crate) age: Option<Duration>,
     pub(crate) status: RemovalStatus,
+    /// Why the resource was selected for removal, if not (only) because of its age or labels.
+    #[tabled(display = "display_reason")]
+    pub(crate) reason: Option<&'static str>,
+}
+
+fn display_reason(reason: &Option<&'static str>) -> String {
+    reason.unwrap_or_default().to_string()
+}
+
+/// Formats a size in bytes for display using decimal (SI) units, as the Docker CLI does.
+fn display_size(size: &Option<u64>) -> String {
+    const UNITS: [&str; 5] = ["B", "kB", "MB", "GB", "TB"];
+    let Some(size) = *size else {
+        return String::new();
+    };
+    let mut value = size as f64;
+    let mut unit = 0;
+    while value >= 1000.0 && unit < UNITS.len() - 1 {
+        value /= 1000.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} {}", size, UNITS[unit])
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
+
+/// Formats an age for display as a Go-style duration with a resolution of one second.
+pub(crate) fn display_age(age: &Option<Duration>) -> String {
+    let Some(age) = *age else {
+        return String::new();
+    };
+    let secs = age.as_secs();
+    let (hours, minutes, secs) = (secs / 3600, secs / 60 % 60, secs % 60);
+    if hours > 0 {
+        format!("{}h{}m{}s", hours, minutes, secs)
+    } else if minutes > 0 {
+        format!("{}m{}s", minutes, secs)
+    } else {
+        format!("{}s", secs)
+    }
 }
 
 impl PartialEq for Resource {
@@ -147,16 +1468,27 @@ impl PartialEq for Resource {
 }
 
 impl Resource {
-    /// Attempts to remove this resource.
-    /// After completion, the resource's `status` will be either `RemovalStatus::Success` or
-    /// `RemovalStatus::Error`.
-    async fn remove(&mut self, docker: &Docker) {
+    /// Attempts to remove this resource, unless it is not `RemovalStatus::Eligible`.
+    /// After completion, the resource's `status` will be either `RemovalStatus::Success`,
+    /// `RemovalStatus::InProgress`, or `RemovalStatus::Error`.
+    pub(crate) async fn remove(&mut self, docker: &Docker, options: &RemovalOptions<'_>) {
+        if !matches!(self.status, RemovalStatus::Eligible) {
+            return;
+        }
+        let _permit = match options.throttle {
+            Some(throttle) => throttle.acquire().await,
+            None => None,
+        };
         debug!("Removing {} {}", self.resource_type, self.name);
         use bollard::errors::Error::DockerResponseServerError;
         match self.resource_type {
             ResourceType::Container => {
+                if options.stop_containers() {
+                    self.stop(docker, options).await;
+                }
                 let options = RemoveContainerOptions {
                     force: true,
+                    v: options.remove_container_volumes,
                     ..Default::default()
                 };
                 match docker.remove_container(&self.id, Some(options)).await {
@@ -188,16 +1520,20 @@ impl Resource {
                         // Mark as successful if already removed (404)
                         self.status = RemovalStatus::Success;
                     }
+                    // A conflict (409) indicates that the network still has active endpoints
                     Err(DockerResponseServerError {
                         status_code: 409, ..
                     }) => {
-                        self.status = RemovalStatus::InProgress;
+                        self.status = RemovalStatus::InUse;
                     }
                     Err(e) => self.status = RemovalStatus::Error(RemovalError::Docker(e)),
                 };
             }
             ResourceType::Volume => {
-                match docker.remove_volume(&self.id, None).await {
+                match docker
+                    .remove_volume(&self.id, None::<RemoveVolumeOptions>)
+                    .await
+                {
                     Ok(_) => {
                         self.status = RemovalStatus::Success;
                     }
@@ -207,16 +1543,118 @@ impl Resource {
                         // Mark as successful if already removed (404)
                         self.status = RemovalStatus::Success;
                     }
+                    // A conflict (409) indicates that the volume is still mounted by a container
                     Err(DockerResponseServerError {
                         status_code: 409, ..
                     }) => {
-                        self.status = RemovalStatus::InProgress;
+                        self.status = RemovalStatus::InUse;
+                    }
+                    Err(e) => self.status = RemovalStatus::Error(RemovalError::Docker(e)),
+                }
+            }
+            ResourceType::Image => {
+                let options = RemoveImageOptions {
+                    force: options.force_images,
+                    ..Default::default()
+                };
+                match docker.remove_image(&self.id, Some(options), None).await {
+                    Ok(_) => {
+                        self.status = RemovalStatus::Success;
+                    }
+                    Err(DockerResponseServerError {
+                        status_code: 404, ..
+                    }) => {
+                        // Mark as successful if already removed (404)
+                        self.status = RemovalStatus::Success;
+                    }
+                    // A conflict (409) indicates that the image is still referenced, e.g. by
+                    // multiple tags without `force`, so it is reported as an error.
+                    Err(e) => self.status = RemovalStatus::Error(RemovalError::Docker(e)),
+                }
+            }
+            ResourceType::BuildCache => {
+                // There is no endpoint for removing a single build cache record, so prune with a
+                // filter matching only this record instead. Pruning silently skips records which
+                // have come into use since they were listed, so check that it was deleted.
+                let options = PruneBuildOptions {
+                    all: Some(true),
+                    filters: Some(HashMap::from([(String::from("id"), vec![self.id.clone()])])),
+                    ..Default::default()
+                };
+                match docker.prune_build(Some(options)).await {
+                    Ok(response) => {
+                        let deleted = response
+                            .caches_deleted
+                            .is_some_and(|ids| ids.contains(&self.id));
+                        if deleted {
+                            self.status = RemovalStatus::Success;
+                        } else {
+                            debug!("Skipped build cache record {}: not pruned", self.id);
+                            self.status = RemovalStatus::InUse;
+                        }
                     }
                     Err(e) => self.status = RemovalStatus::Error(RemovalError::Docker(e)),
                 }
             }
         }
     }
+
+    /// Marks this network as `RemovalStatus::InUse` if it has any connected containers.
+    async fn check_unused(&mut self, docker: &Docker) {
+        if !matches!(self.status, RemovalStatus::Eligible) {
+            return;
+        }
+        // Connected containers are only included when inspecting networks, not when listing them
+        match docker
+            .inspect_network(&self.id, None::<InspectNetworkOptions>)
+            .await
+        {
+            Ok(network) => {
+                if network
+                    .containers
+                    .is_some_and(|containers| !containers.is_empty())
+                {
+                    debug!("Skipped network {}: in use", self.name);
+                    self.status = RemovalStatus::InUse;
+                }
+            }
+            // A network which no longer exists is handled like any other during removal
+            Err(bollard::errors::Error::DockerResponseServerError {
+                status_code: 404, ..
+            }) => {}
+            Err(e) => self.status = RemovalStatus::Error(RemovalError::Docker(e)),
+        }
+    }
+
+    /// Stops this container, giving it a chance to exit gracefully before it is force-removed.
+    /// Failures are logged but otherwise ignored, since the container is force-removed anyway.
+    async fn stop(&self, docker: &Docker, options: &RemovalOptions<'_>) {
+        debug!("Stopping container {}", self.name);
+        let stop_options = StopContainerOptions {
+            signal: options.stop_signal.map(String::from),
+            // Docker only accepts whole seconds, so round up rather than killing immediately
+            t: options.stop_timeout.map(|timeout| {
+                (timeout.as_secs() + u64::from(timeout.subsec_nanos() > 0))
+                    .try_into()
+                    .unwrap_or(i32::MAX)
+            }),
+        };
+        // The request only completes once the container has exited, which may take longer than
+        // the default request timeout.
+        let docker = match options.stop_timeout {
+            Some(timeout) => &docker.clone().with_timeout(timeout + STOP_REQUEST_TIMEOUT),
+            None => docker,
+        };
+        match docker.stop_container(&self.id, Some(stop_options)).await {
+            // Stopping a container which is not running succeeds (304), and removing one which
+            // no longer exists (404) is handled by the caller.
+            Ok(_)
+            | Err(bollard::errors::Error::DockerResponseServerError {
+                status_code: 404, ..
+            }) => {}
+            Err(e) => warn!("Failed to stop container {}: {}", self.name, e),
+        }
+    }
 }
 
 /// Error encountered while removing a resource.
@@ -226,17 +1664,104 @@ pub(crate) enum RemovalError {
     Docker(#[from] bollard::errors::Error),
 }
 
+/// Upper bound on the number of resources removed in a single run, guarding against filters which
+/// match far more than intended.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum RemovalLimit {
+    /// At most this many resources.
+    Count(usize),
+    /// At most this percentage of all resources of the same type, regardless of any filters.
+    Percent(f64),
+}
+
+impl RemovalLimit {
+    /// Maximum number of resources of the given type which may be removed.
+    async fn max(self, docker: &Docker, resource_type: &ResourceType) -> Result<usize, ReapError> {
+        let percent = match self {
+            Self::Count(count) => return Ok(count),
+            Self::Percent(percent) => percent,
+        };
+        let total = match resource_type {
+            ResourceType::Container => docker
+                .list_containers(Some(ListContainersOptions {
+                    all: true,
+                    ..Default::default()
+                }))
+                .await?
+                .len(),
+            ResourceType::Network => docker
+                .list_networks(None::<ListNetworksOptions>)
+                .await?
+                .len(),
+            ResourceType::Volume => docker
+                .list_volumes(None::<ListVolumesOptions>)
+                .await?
+                .volumes
+                .map_or(0, |volumes| volumes.len()),
+            ResourceType::Image => docker.list_images(None::<ListImagesOptions>).await?.len(),
+            ResourceType::BuildCache => docker
+                .df(None::<DataUsageOptions>)
+                .await?
+                .build_cache
+                .map_or(0, |records| records.len()),
+        };
+        Ok((total as f64 * percent / 100.0).floor() as usize)
+    }
+}
+
+/// Aborts the run if more resources of the given type would be removed than the limit allows.
+/// Resources which are skipped, as well as those of other types (such as the networks and volumes
+/// associated with reaped containers), do not count towards the limit.
+async fn check_removal_limit(
+    docker: &Docker,
+    resources: Vec<Resource>,
+    resource_type: ResourceType,
+    limit: Option<RemovalLimit>,
+) -> Result<Vec<Resource>, ReapError> {
+    let Some(limit) = limit else {
+        return Ok(resources);
+    };
+    let count = resources
+        .iter()
+        .filter(|resource| {
+            resource.resource_type == resource_type
+                && matches!(resource.status, RemovalStatus::Eligible)
+        })
+        .count();
+    if count == 0 {
+        return Ok(resources);
+    }
+    let max = limit.max(docker, &resource_type).await?;
+    if count > max {
+        return Err(ReapError::TooManyRemovals {
+            count,
+            max,
+            resources,
+        });
+    }
+    Ok(resources)
+}
+
 /// Unrecoverable error encountered during a reap iteration.
 #[derive(Error, Debug)]
 pub(crate) enum ReapError {
     #[error(transparent)]
     Docker(#[from] bollard::errors::Error),
     #[error(transparent)]
-    InvalidSystemTime(#[from] std::time::SystemTimeError),
-    #[error(transparent)]
     TaskFailure(#[from] tokio::task::JoinError),
     #[error("min_age must be less than max_age")]
     InvalidAgeBound,
+    #[error("group_by_label and keep_newest must be specified together")]
+    IncompleteGrouping,
+    #[error("failed to determine disk usage: {0}")]
+    DiskUsage(std::io::Error),
+    #[error("refusing to remove {count} resources, more than the limit of {max}")]
+    TooManyRemovals {
+        count: usize,
+        max: usize,
+        /// The resources which would have been removed.
+        resources: Vec<Resource>,
+    },
 }
 
 pub(crate) async fn reap_containers(
@@ -246,71 +1771,100 @@ pub(crate) async fn reap_containers(
     if config.min_age.unwrap_or(Duration::ZERO) >= config.max_age.unwrap_or(Duration::MAX) {
         return Err(ReapError::InvalidAgeBound);
     }
+    if config.group_by_label.is_some() != config.keep_newest.is_some() {
+        return Err(ReapError::IncompleteGrouping);
+    }
 
     // Collect eligible containers. Since there's no way to ask the Docker API for containers
     // matching a certain age range directly, we first obtain the full list based only on the
     // provided filter values (if any).
-    let mut eligible_containers = docker
-        .list_containers(Some(ListContainersOptions {
-            all: true,
-            filters: config.filters.to_bollard_filters(),
-            ..Default::default()
-        }))
-        .await?;
+    let mut eligible_containers = list_containers(docker, config.filters, config.age_from).await?;
+    retain_by_name(&mut eligible_containers, &config.name_patterns());
+    retain_unexcluded(&mut eligible_containers, config.excludes);
+    retain_by_expression(&mut eligible_containers, config.match_expression);
+
+    // Activity is sampled for all matching containers, so that their idle time is already known
+    // once they reach the specified age.
+    let idle_ids: Option<HashSet<String>> = match &config.idle {
+        Some(idle) => Some(
+            idle.idle_containers(docker, eligible_containers.iter().filter_map(|c| c.id()))
+                .await
+                .into_iter()
+                .map(String::from)
+                .collect(),
+        ),
+        None => None,
+    };
+
+    // The newest containers in each group are determined among all matching containers, not just
+    // those within the specified age range.
+    let matched_count = eligible_containers.len();
+    if let Some(label) = config.group_by_label
+        && let Some(keep) = config.keep_newest
+    {
+        retain_all_but_newest(&mut eligible_containers, label, keep);
+    }
+    let kept_count = matched_count - eligible_containers.len();
 
     // Reduce the eligible containers to only those within the specified age range (if applicable).
-    if config.max_age.is_some() || config.min_age.is_some() {
-        let now: Duration = SystemTime::now().duration_since(UNIX_EPOCH)?;
+    let criteria = config.age_criteria();
+    let evicted_ids = retain_by_age_or_count(
+        &mut eligible_containers,
+        &criteria,
+        config.max_count,
+        kept_count,
+        |c| config.protection_label.protects(c.labels()),
+        |c| {
+            idle_ids
+                .as_ref()
+                .is_some_and(|idle_ids| c.id().is_none_or(|id| !idle_ids.contains(id)))
+        },
+    );
+
+    // Evicted containers are removed regardless of their activity, just as regardless of their age
+    if let Some(idle_ids) = idle_ids {
         eligible_containers.retain(|container| {
-            let id = container.id.as_deref().unwrap_or("unknown ID");
-            // The creation time for containers is returned as a signed UNIX timestamp, but we need
-            // to convert it to an unsigned value to use `Duration::from_secs()`. If, for some
-            // reason, the returned creation time is missing or negative, skip the container.
-            let Some(creation_secs) = container.created else {
-                warn!("Skipped container {}: missing creation timestamp", id);
-                return false;
-            };
-            let creation_secs: u64 = match creation_secs.try_into() {
-                Ok(secs) => secs,
-                Err(_) => {
-                    warn!("Skipped container {}: negative creation timestamp", id);
-                    return false;
-                }
-            };
-            let Some(age) = now.checked_sub(Duration::from_secs(creation_secs)) else {
-                warn!(
-                    "Skipped container {}: creation timestamp after system time",
-                    id
-                );
-                return false;
-            };
-            let within_age_range = age > config.min_age.unwrap_or(Duration::ZERO)
-                && age < config.max_age.unwrap_or(Duration::MAX);
-            if !within_age_range {
-                debug!("Skipped container {}: age outside of specified range", id);
-            }
-            within_age_range
+            container
+                .id()
+                .is_some_and(|id| idle_ids.contains(id) || evicted_ids.contains(id))
         });
     }
 
     let mut eligible_network_names = HashSet::new();
+    let mut eligible_volume_names = HashSet::new();
     let mut eligible_resources: Vec<Resource> = Vec::new();
     for container in eligible_containers {
+        let age = container.age();
+        let container = container.summary;
         let Some(id) = container.id else {
             warn!("Skipped container (unknown ID): missing ID value");
             continue;
         };
+        let name = container
+            .names
+            .unwrap_or_default()
+            .first()
+            .unwrap_or(&id)
+            .clone();
+        let status = protection_status(
+            &ResourceType::Container,
+            &name,
+            config.protection_label.protects(container.labels.as_ref()),
+        );
+        // The networks and volumes of protected containers remain in use
+        let protected = matches!(status, RemovalStatus::Protected);
         eligible_resources.push(Resource {
             resource_type: ResourceType::Container,
             id: id.clone(),
-            name: container
-                .names
-                .unwrap_or_default()
-                .first()
-                .unwrap_or(&id)
-                .clone(),
-            status: RemovalStatus::Eligible,
+            name,
+            size: None,
+            age,
+            status,
+            reason: evicted_ids.contains(&id).then_some(EVICTED_REASON),
         });
+        if protected {
+            continue;
+        }
         if config.reap_networks
             && let Some(network_settings) = container.network_settings
             && let Some(networks) = network_settings.networks
@@ -321,29 +1875,105 @@ pub(crate) async fn reap_containers(
                 debug!("Added network {} from container {} ", name, id);
             }))
         }
+        if let Some(mode) = config.reap_volumes
+            && let Some(mounts) = container.mounts
+        {
+            eligible_volume_names.extend(
+                mounts
+                    .into_iter()
+                    .filter(|mount| mount.typ == Some(MountPointTypeEnum::VOLUME))
+                    .filter_map(|mount| mount.name)
+                    .filter(|name| mode == ReapVolumesMode::All || is_anonymous_volume(name))
+                    .inspect(|name| {
+                        debug!("Added volume {} from container {} ", name, id);
+                    }),
+            )
+        }
     }
+    // Associated networks and volumes are not listed, so look up which of them are protected
+    let labeled_network_names: HashSet<String> = if eligible_network_names.is_empty() {
+        HashSet::new()
+    } else {
+        docker
+            .list_networks(Some(ListNetworksOptions {
+                filters: Some(vec![config.protection_label.to_filter()].to_bollard_filters()),
+            }))
+            .await?
+            .into_iter()
+            .filter_map(|network| network.name)
+            .collect()
+    };
+    let labeled_volume_names: HashSet<String> = if eligible_volume_names.is_empty() {
+        HashSet::new()
+    } else {
+        docker
+            .list_volumes(Some(ListVolumesOptions {
+                filters: Some(vec![config.protection_label.to_filter()].to_bollard_filters()),
+            }))
+            .await?
+            .volumes
+            .unwrap_or_default()
+            .into_iter()
+            .map(|volume| volume.name)
+            .collect()
+    };
     for network_name in eligible_network_names {
         eligible_resources.push(Resource {
             resource_type: ResourceType::Network,
             id: network_name.clone(),
-            name: network_name.clone(),
-            status: RemovalStatus::Eligible,
+            status: network_status(
+                &network_name,
+                labeled_network_names.contains(&network_name),
+                config.protected_networks,
+            ),
+            name: network_name,
+            size: None,
+            age: None,
+            reason: None,
+        })
+    }
+    for volume_name in eligible_volume_names {
+        eligible_resources.push(Resource {
+            resource_type: ResourceType::Volume,
+            id: volume_name.clone(),
+            status: protection_status(
+                &ResourceType::Volume,
+                &volume_name,
+                labeled_volume_names.contains(&volume_name),
+            ),
+            name: volume_name,
+            size: None,
+            age: None,
+            reason: None,
         })
     }
+    let eligible_resources = check_removal_limit(
+        docker,
+        eligible_resources,
+        ResourceType::Container,
+        config.max_removals,
+    )
+    .await?;
     if config.dry_run {
         return Ok(eligible_resources);
     }
-    // Remove containers before networks, as otherwise there will be active endpoints
+    // Remove containers before networks and volumes, as otherwise they would still be in use
+    let options = &config.removal_options();
     let mut container_futures = Vec::new();
     let mut network_futures = Vec::new();
+    let mut volume_futures = Vec::new();
     for mut resource in eligible_resources {
         match resource.resource_type {
             ResourceType::Container => container_futures.push(async move {
-                resource.remove(docker).await;
+                resource.remove(docker, options).await;
                 resource
             }),
             ResourceType::Network => network_futures.push(async move {
-                resource.remove(docker).await;
+                resource.remove(docker, options).await;
+                resource
+            }),
+            ResourceType::Volume => volume_futures.push(async move {
+                resource.remove(docker, options).await;
                 resource
             }),
             _ => {}
@@ -351,6 +1981,7 @@ pub(crate) async fn reap_containers(
     }
     let mut removed_resources = futures::future::join_all(container_futures).await;
     removed_resources.extend(futures::future::join_all(network_futures).await);
+    removed_resources.extend(futures::future::join_all(volume_futures).await);
     Ok(removed_resources)
 }
 
@@ -364,46 +1995,37 @@ pub(crate) async fn reap_networks(
 
     let mut eligible_networks = docker
         .list_networks(Some(ListNetworksOptions {
-            filters: config.filters.to_bollard_filters(),
+            filters: Some(config.filters.to_bollard_filters()),
         }))
         .await?;
+    retain_by_name(&mut eligible_networks, &config.name_patterns());
+    retain_unexcluded(&mut eligible_networks, config.excludes);
+    retain_by_expression(&mut eligible_networks, config.match_expression);
 
-    if config.max_age.is_some() || config.min_age.is_some() {
-        let now = chrono::Utc::now();
-        eligible_networks.retain(|network| {
-            let Some(ref name) = network.name else {
-                warn!("Skipped network (unknown name): missing name value");
-                return false;
-            };
-            let Some(ref creation_timestamp) = network.created else {
-                warn!("Skipped network {}: missing creation timestamp", name);
-                return false;
-            };
-            let Ok(creation_time) = chrono::DateTime::parse_from_rfc3339(creation_timestamp) else {
-                warn!(
-                    "Skipped network {}: failed to parse creation timestamp as RFC3339",
-                    name
-                );
-                return false;
-            };
-            let Ok(age) = now.signed_duration_since(creation_time).to_std() else {
-                warn!(
-                    "Skipped network {}: creation timestamp after system time",
-                    name
-                );
-                return false;
-            };
-            let within_age_range = age > config.min_age.unwrap_or(Duration::ZERO)
-                && age < config.max_age.unwrap_or(Duration::MAX);
-            if !within_age_range {
-                debug!("Skipped network {}: age outside of specified range", name);
-            }
-            within_age_range
-        });
-    }
+    let criteria = config.age_criteria();
+    let evicted_ids = retain_by_age_or_count(
+        &mut eligible_networks,
+        &criteria,
+        config.max_count,
+        0,
+        |n| {
+            is_protected_network(
+                n.name.as_deref().unwrap_or_default(),
+                config.protection_label.protects(n.labels()),
+                config.protected_networks,
+            )
+        },
+        |_| false,
+    );
     let eligible_networks: Vec<Resource> = eligible_networks
         .into_iter()
         .filter_map(|network| {
+            let age = network.age();
+            let labeled = config.protection_label.protects(network.labels());
+            let evicted = network
+                .id
+                .as_ref()
+                .is_some_and(|id| evicted_ids.contains(id));
             let Some(name) = network.name else {
                 warn!("Skipped network (unknown name): missing name value");
                 return None;
@@ -411,16 +2033,36 @@ pub(crate) async fn reap_networks(
             Some(Resource {
                 resource_type: ResourceType::Network,
                 id: name.clone(),
+                status: network_status(&name, labeled, config.protected_networks),
                 name,
-                status: RemovalStatus::Eligible,
+                size: None,
+                age,
+                reason: evicted.then_some(EVICTED_REASON),
             })
         })
         .collect();
+    let eligible_networks = if config.unused_only {
+        let network_futures = eligible_networks.into_iter().map(|mut network| async move {
+            network.check_unused(docker).await;
+            network
+        });
+        futures::future::join_all(network_futures).await
+    } else {
+        eligible_networks
+    };
+    let eligible_networks = check_removal_limit(
+        docker,
+        eligible_networks,
+        ResourceType::Network,
+        config.max_removals,
+    )
+    .await?;
     if config.dry_run {
         return Ok(eligible_networks);
     }
+    let options = &config.removal_options();
     let network_futures = eligible_networks.into_iter().map(|mut network| async move {
-        network.remove(docker).await;
+        network.remove(docker, options).await;
         network
     });
     let removed_networks = futures::future::join_all(network_futures).await;
@@ -440,7 +2082,7 @@ pub(crate) async fn reap_volumes(
         warnings,
     } = docker
         .list_volumes(Some(ListVolumesOptions {
-            filters: config.filters.to_bollard_filters(),
+            filters: Some(config.filters.to_bollard_filters()),
         }))
         .await?;
     if let Some(warnings) = warnings {
@@ -452,55 +2094,318 @@ pub(crate) async fn reap_volumes(
         debug!("No volumes returned");
         return Ok(Vec::new());
     };
+    retain_by_name(&mut eligible_volumes, &config.name_patterns());
+    retain_unexcluded(&mut eligible_volumes, config.excludes);
+    retain_by_expression(&mut eligible_volumes, config.match_expression);
+
+    let criteria = config.age_criteria();
+    let evicted_ids = retain_by_age_or_count(
+        &mut eligible_volumes,
+        &criteria,
+        config.max_count,
+        0,
+        |v| config.protection_label.protects(v.labels()),
+        |_| false,
+    );
+    // Volumes cannot be removed while any container (running or not) still mounts them, so
+    // optionally skip these up front rather than reporting a conflict for each one.
+    let used_volume_names: HashSet<String> = if config.unused_only {
+        docker
+            .list_containers(Some(ListContainersOptions {
+                all: true,
+                ..Default::default()
+            }))
+            .await?
+            .into_iter()
+            .flat_map(|container| container.mounts.unwrap_or_default())
+            .filter(|mount| mount.typ == Some(MountPointTypeEnum::VOLUME))
+            .filter_map(|mount| mount.name)
+            .collect()
+    } else {
+        HashSet::new()
+    };
+    let eligible_volumes: Vec<Resource> = eligible_volumes
+        .into_iter()
+        .map(|volume| Resource {
+            resource_type: ResourceType::Volume,
+            age: volume.age(),
+            id: volume.name.clone(),
+            status: if config.protection_label.protects(volume.labels()) {
+                protection_status(&ResourceType::Volume, &volume.name, true)
+            } else if used_volume_names.contains(&volume.name) {
+                debug!("Skipped volume {}: in use by a container", volume.name);
+                RemovalStatus::InUse
+            } else {
+                RemovalStatus::Eligible
+            },
+            reason: evicted_ids.contains(&volume.name).then_some(EVICTED_REASON),
+            name: volume.name,
+            size: None,
+        })
+        .collect();
+    let eligible_volumes = check_removal_limit(
+        docker,
+        eligible_volumes,
+        ResourceType::Volume,
+        config.max_removals,
+    )
+    .await?;
+    if config.dry_run {
+        return Ok(eligible_volumes);
+    }
+    let options = &config.removal_options();
+    let volume_futures = eligible_volumes.into_iter().map(|mut volume| async move {
+        volume.remove(docker, options).await;
+        volume
+    });
+    let removed_volumes = futures::future::join_all(volume_futures).await;
+    Ok(removed_volumes)
+}
+
+/// Determines when each container matching the configured filters will become eligible for
+/// reaping.
+pub(crate) async fn container_expirations(
+    docker: &Docker,
+    config: &ReapContainersConfig<'_>,
+) -> Result<Vec<Expiration>, ReapError> {
+    let mut containers = list_containers(docker, config.filters, config.age_from).await?;
+    retain_by_name(&mut containers, &config.name_patterns());
+    retain_unexcluded(&mut containers, config.excludes);
+    Ok(expirations(&containers, &config.age_criteria()))
+}
+
+/// Determines when each network matching the configured filters will become eligible for reaping.
+pub(crate) async fn network_expirations(
+    docker: &Docker,
+    config: &ReapNetworksConfig<'_>,
+) -> Result<Vec<Expiration>, ReapError> {
+    let mut networks = docker
+        .list_networks(Some(ListNetworksOptions {
+            filters: Some(config.filters.to_bollard_filters()),
+        }))
+        .await?;
+    retain_by_name(&mut networks, &config.name_patterns());
+    retain_unexcluded(&mut networks, config.excludes);
+    Ok(expirations(&networks, &config.age_criteria()))
+}
+
+/// Determines when each volume matching the configured filters will become eligible for reaping.
+pub(crate) async fn volume_expirations(
+    docker: &Docker,
+    config: &ReapVolumesConfig<'_>,
+) -> Result<Vec<Expiration>, ReapError> {
+    let mut volumes = docker
+        .list_volumes(Some(ListVolumesOptions {
+            filters: Some(config.filters.to_bollard_filters()),
+        }))
+        .await?
+        .volumes
+        .unwrap_or_default();
+    retain_by_name(&mut volumes, &config.name_patterns());
+    retain_unexcluded(&mut volumes, config.excludes);
+    Ok(expirations(&volumes, &config.age_criteria()))
+}
+
+pub(crate) async fn reap_images(
+    docker: &Docker,
+    config: &ReapImagesConfig<'_>,
+) -> Result<Vec<Resource>, ReapError> {
+    if config.min_age.unwrap_or(Duration::ZERO) >= config.max_age.unwrap_or(Duration::MAX) {
+        return Err(ReapError::InvalidAgeBound);
+    }
+
+    let mut eligible_images = docker
+        .list_images(Some(ListImagesOptions {
+            filters: Some(config.filters.to_bollard_filters()),
+            ..Default::default()
+        }))
+        .await?;
+
+    // Images cannot be removed while any container (running or not) still references them, so
+    // skip these up front rather than reporting a conflict for each one.
+    let used_image_ids: HashSet<String> = docker
+        .list_containers(Some(ListContainersOptions {
+            all: true,
+            ..Default::default()
+        }))
+        .await?
+        .into_iter()
+        .filter_map(|container| container.image_id)
+        .collect();
+    eligible_images.retain(|image| {
+        let in_use = used_image_ids.contains(&image.id);
+        if in_use {
+            debug!("Skipped image {}: in use by a container", image.id);
+        }
+        !in_use
+    });
+
+    let criteria = AgeCriteria {
+        min_age: config.min_age,
+        max_age: config.max_age,
+        ..Default::default()
+    };
+    retain_by_age(&mut eligible_images, &criteria);
+    let eligible_images: Vec<Resource> = eligible_images
+        .into_iter()
+        .map(|image| {
+            let labeled = config.protection_label.protects(image.labels());
+            // Untagged images are reported as `<none>:<none>`, which is not a useful name.
+            let name = image
+                .repo_tags
+                .iter()
+                .find(|tag| *tag != "<none>:<none>")
+                .unwrap_or(&image.id)
+                .clone();
+            Resource {
+                resource_type: ResourceType::Image,
+                age: image.age(),
+                status: protection_status(&ResourceType::Image, &name, labeled),
+                name,
+                id: image.id,
+                size: None,
+                reason: None,
+            }
+        })
+        .collect();
+    let eligible_images = check_removal_limit(
+        docker,
+        eligible_images,
+        ResourceType::Image,
+        config.max_removals,
+    )
+    .await?;
+    if config.dry_run {
+        return Ok(eligible_images);
+    }
+    let options = &config.removal_options();
+    let image_futures = eligible_images.into_iter().map(|mut image| async move {
+        image.remove(docker, options).await;
+        image
+    });
+    let removed_images = futures::future::join_all(image_futures).await;
+    Ok(removed_images)
+}
+
+/// Reduces the build cache records to those which are not in use and whose age at time `now`,
+/// measured from the timestamp selected by `age_from`, is within the configured range (if any).
+pub(crate) fn retain_eligible_records(
+    records: &mut Vec<BuildCache>,
+    config: &ReapBuildCacheConfig<'_>,
+    now: DateTime<Utc>,
+) {
+    records.retain(|record| {
+        let Some(ref id) = record.id else {
+            warn!("Skipped build cache record (unknown ID): missing ID value");
+            return false;
+        };
+        if record.in_use.unwrap_or(false) {
+            debug!("Skipped build cache record {}: in use", id);
+            return false;
+        }
+        true
+    });
 
-    if config.max_age.is_some() || config.min_age.is_some() {
-        let now = chrono::Utc::now();
-        eligible_volumes.retain(|volume| {
-            let Some(ref creation_timestamp) = volume.created_at else {
-                warn!("Skipped volume {}: missing creation timestamp", volume.name);
+    let criteria = AgeCriteria {
+        min_age: config.min_age,
+        max_age: config.max_age,
+        ..Default::default()
+    };
+    if criteria.is_set() {
+        records.retain(|record| {
+            let id = record.id.as_deref().unwrap_or("unknown ID");
+            let timestamp = match config.age_from {
+                BuildCacheAgeFrom::Created => record.created_at.as_ref(),
+                BuildCacheAgeFrom::LastUsed => {
+                    record.last_used_at.as_ref().or(record.created_at.as_ref())
+                }
+            };
+            let Some(timestamp) = timestamp else {
+                warn!("Skipped build cache record {}: missing timestamp", id);
+                crate::monitoring::record_skip(BUILD_CACHE_KIND, "invalid_timestamp");
                 return false;
             };
-            let Ok(creation_time) = chrono::DateTime::parse_from_rfc3339(creation_timestamp) else {
+            let Ok(time) = DateTime::parse_from_rfc3339(timestamp) else {
                 warn!(
-                    "Skipped volume {}: failed to parse creation timestamp as RFC3339",
-                    volume.name
+                    "Skipped build cache record {}: failed to parse timestamp as RFC3339",
+                    id
                 );
+                crate::monitoring::record_skip(BUILD_CACHE_KIND, "invalid_timestamp");
                 return false;
             };
-            let Ok(age) = now.signed_duration_since(creation_time).to_std() else {
+            let Ok(age) = now.signed_duration_since(time).to_std() else {
                 warn!(
-                    "Skipped volume {}: creation timestamp after system time",
-                    volume.name
+                    "Skipped build cache record {}: timestamp after system time",
+                    id
                 );
+                crate::monitoring::record_skip(BUILD_CACHE_KIND, "invalid_timestamp");
                 return false;
             };
-            let within_age_range = age > config.min_age.unwrap_or(Duration::ZERO)
-                && age < config.max_age.unwrap_or(Duration::MAX);
-            if !within_age_range {
-                debug!(
-                    "Skipped volume {}: age outside of specified range",
-                    volume.name
-                );
-            }
-            within_age_range
-        })
+            criteria.is_eligible(BUILD_CACHE_KIND, id, age, None)
+        });
     }
-    let eligible_volumes: Vec<Resource> = eligible_volumes
+}
+
+pub(crate) async fn reap_build_cache(
+    docker: &Docker,
+    config: &ReapBuildCacheConfig<'_>,
+) -> Result<Vec<Resource>, ReapError> {
+    if config.min_age.unwrap_or(Duration::ZERO) >= config.max_age.unwrap_or(Duration::MAX) {
+        return Err(ReapError::InvalidAgeBound);
+    }
+
+    // Build cache records are only exposed through the disk usage endpoint.
+    let Some(mut eligible_records) = docker
+        .df(Some(DataUsageOptions {
+            _type: Some(vec![String::from("build-cache")]),
+        }))
+        .await?
+        .build_cache
+    else {
+        debug!("No build cache records returned");
+        return Ok(Vec::new());
+    };
+
+    retain_eligible_records(&mut eligible_records, config, Utc::now());
+    let eligible_records: Vec<Resource> = eligible_records
         .into_iter()
-        .map(|volume| Resource {
-            resource_type: ResourceType::Volume,
-            id: volume.name.clone(),
-            name: volume.name,
-            status: RemovalStatus::Eligible,
+        .filter_map(|record| {
+            let id = record.id?;
+            Some(Resource {
+                resource_type: ResourceType::BuildCache,
+                name: id.clone(),
+                id,
+                // Shared records are also referenced by other records, so removing them would
+                // not free any space.
+                size: if record.shared.unwrap_or(false) {
+                    Some(0)
+                } else {
+                    record.size.and_then(|size| size.try_into().ok())
+                },
+                age: record
+                    .created_at
+                    .and_then(|created| DateTime::parse_from_rfc3339(&created).ok())
+                    .and_then(|created| Utc::now().signed_duration_since(created).to_std().ok()),
+                status: RemovalStatus::Eligible,
+                reason: None,
+            })
         })
         .collect();
+    let eligible_records = check_removal_limit(
+        docker,
+        eligible_records,
+        ResourceType::BuildCache,
+        config.max_removals,
+    )
+    .await?;
     if config.dry_run {
-        return Ok(eligible_volumes);
+        return Ok(eligible_records);
     }
-    let volume_futures = eligible_volumes.into_iter().map(|mut volume| async move {
-        volume.remove(docker).await;
-        volume
+    let options = &config.removal_options();
+    let record_futures = eligible_records.into_iter().map(|mut record| async move {
+        record.remove(docker, options).await;
+        record
     });
-    let removed_volumes = futures::future::join_all(volume_futures).await;
-    Ok(removed_volumes)
+    let removed_records = futures::future::join_all(record_futures).await;
+    Ok(removed_records)
 }
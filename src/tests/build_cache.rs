@@ -0,0 +1,185 @@
+//! Build cache reaping tests.
+//!
+//! Build cache records can only be created by BuildKit, which requires a build session that the
+//! Docker API client does not provide. Which records are selected is therefore tested with
+//! constructed records, while the tests against the Docker host work with whatever records
+//! already exist there, and never remove any of them.
+
+use std::collections::HashSet;
+
+use super::common::docker_client;
+use crate::reaper::{
+    BuildCacheAgeFrom, ReapBuildCacheConfig, ReapError, RemovalStatus, reap_build_cache,
+    retain_eligible_records,
+};
+use bollard::models::BuildCache;
+use bollard::query_parameters::DataUsageOptions;
+use chrono::{TimeDelta, Utc};
+use serial_test::serial;
+use tokio::time::Duration;
+
+/// Create a build cache record as returned by the disk usage endpoint.
+fn record(id: &str, in_use: bool, created: TimeDelta, last_used: Option<TimeDelta>) -> BuildCache {
+    let now = Utc::now();
+    BuildCache {
+        id: Some(id.to_string()),
+        in_use: Some(in_use),
+        created_at: Some((now - created).to_rfc3339()),
+        last_used_at: last_used.map(|last_used| (now - last_used).to_rfc3339()),
+        ..Default::default()
+    }
+}
+
+/// Reduce the given records according to the age options, returning the IDs of those retained.
+fn eligible_ids(
+    records: &[BuildCache],
+    min_age: Option<Duration>,
+    max_age: Option<Duration>,
+    age_from: BuildCacheAgeFrom,
+) -> Vec<String> {
+    let mut records = records.to_vec();
+    retain_eligible_records(
+        &mut records,
+        &ReapBuildCacheConfig {
+            dry_run: true,
+            min_age,
+            max_age,
+            age_from,
+            max_removals: None,
+            throttle: None,
+        },
+        Utc::now(),
+    );
+    records.into_iter().filter_map(|record| record.id).collect()
+}
+
+/// Test that the age of records is measured from the timestamp selected by `age_from`, falling
+/// back to the creation time for records which were never used.
+#[test]
+fn age_from() {
+    let records = [
+        record(
+            "recently-used",
+            false,
+            TimeDelta::hours(3),
+            Some(TimeDelta::minutes(5)),
+        ),
+        record(
+            "long-unused",
+            false,
+            TimeDelta::hours(3),
+            Some(TimeDelta::hours(2)),
+        ),
+        record("never-used", false, TimeDelta::hours(3), None),
+        record("new", false, TimeDelta::minutes(5), None),
+    ];
+    let min_age = Some(Duration::from_secs(60 * 60));
+    assert_eq!(
+        eligible_ids(&records, min_age, None, BuildCacheAgeFrom::LastUsed),
+        ["long-unused", "never-used"]
+    );
+    assert_eq!(
+        eligible_ids(&records, min_age, None, BuildCacheAgeFrom::Created),
+        ["recently-used", "long-unused", "never-used"]
+    );
+    assert_eq!(
+        eligible_ids(&records, None, min_age, BuildCacheAgeFrom::LastUsed),
+        ["recently-used", "new"]
+    );
+}
+
+/// Test that records in use are never selected, regardless of their age.
+#[test]
+fn in_use() {
+    let records = [
+        record(
+            "in-use",
+            true,
+            TimeDelta::hours(3),
+            Some(TimeDelta::hours(2)),
+        ),
+        record(
+            "unused",
+            false,
+            TimeDelta::hours(3),
+            Some(TimeDelta::hours(2)),
+        ),
+    ];
+    assert_eq!(
+        eligible_ids(&records, None, None, BuildCacheAgeFrom::LastUsed),
+        ["unused"]
+    );
+    assert_eq!(
+        eligible_ids(
+            &records,
+            Some(Duration::from_secs(60)),
+            None,
+            BuildCacheAgeFrom::LastUsed
+        ),
+        ["unused"]
+    );
+}
+
+/// List the IDs of all build cache records.
+async fn build_cache_ids() -> HashSet<String> {
+    docker_client()
+        .df(Some(DataUsageOptions {
+            _type: Some(vec![String::from("build-cache")]),
+        }))
+        .await
+        .expect("failed to list build cache records")
+        .build_cache
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|record| record.id)
+        .collect()
+}
+
+/// Test that dry-run mode reports build cache records as eligible without removing them.
+#[tokio::test]
+#[serial]
+async fn dry_run() {
+    let result = reap_build_cache(
+        docker_client(),
+        &ReapBuildCacheConfig {
+            dry_run: true,
+            min_age: None,
+            max_age: None,
+            age_from: BuildCacheAgeFrom::Created,
+            max_removals: None,
+            throttle: None,
+        },
+    )
+    .await
+    .expect("failed to reap build cache");
+    assert!(
+        result
+            .iter()
+            .all(|resource| matches!(resource.status, RemovalStatus::Eligible))
+    );
+    let remaining_ids = build_cache_ids().await;
+    assert!(
+        result
+            .iter()
+            .all(|resource| remaining_ids.contains(&resource.id))
+    );
+}
+
+/// Test that an empty age range is rejected.
+#[tokio::test]
+#[serial]
+async fn invalid_age_bound() {
+    let result = reap_build_cache(
+        docker_client(),
+        &ReapBuildCacheConfig {
+            dry_run: true,
+            min_age: Some(Duration::from_secs(60)),
+            max_age: Some(Duration::from_secs(60)),
+            age_from: BuildCacheAgeFrom::LastUsed,
+            max_removals: None,
+            throttle: None,
+        },
+    )
+    .await;
+    assert!(matches!(result, Err(ReapError::InvalidAgeBound)));
+}
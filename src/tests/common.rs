@@ -2,15 +2,18 @@
 #![allow(dead_code)]
 
 use crate::reaper::{
-    Filter, ReapContainersConfig, ReapNetworksConfig, ReapVolumesConfig, reap_containers,
-    reap_networks, reap_volumes,
+    Filter, ReapContainersConfig, ReapImagesConfig, ReapNetworksConfig, ReapVolumesConfig,
+    ReapVolumesMode, reap_containers, reap_images, reap_networks, reap_volumes,
 };
 use bollard::Docker;
-use bollard::container::{Config, NetworkingConfig};
-use bollard::image::CreateImageOptions;
-use bollard::network::CreateNetworkOptions;
-use bollard::secret::{ContainerCreateResponse, EndpointSettings};
-use bollard::volume::CreateVolumeOptions;
+use bollard::models::{
+    ContainerConfig, ContainerCreateBody, ContainerCreateResponse, EndpointSettings, HostConfig,
+    NetworkCreateRequest, NetworkingConfig, VolumeCreateOptions,
+};
+use bollard::query_parameters::{
+    CommitContainerOptions, CreateContainerOptions, CreateImageOptions, InspectContainerOptions,
+    InspectNetworkOptions, RemoveContainerOptions, StartContainerOptions, StopContainerOptions,
+};
 use chrono::Utc;
 use std::collections::HashMap;
 use std::sync::OnceLock;
@@ -53,7 +56,7 @@ pub(super) async fn run_container(
     if client.inspect_image(&TEST_IMAGE).await.is_err() {
         let mut pull_results_stream = client.create_image(
             Some(CreateImageOptions {
-                from_image: TEST_IMAGE,
+                from_image: Some(TEST_IMAGE.to_string()),
                 ..Default::default()
             }),
             None,
@@ -67,9 +70,9 @@ pub(super) async fn run_container(
     let ContainerCreateResponse {
         id: container_id, ..
     } = client
-        .create_container::<String, String>(
-            None,
-            Config {
+        .create_container(
+            None::<CreateContainerOptions>,
+            ContainerCreateBody {
                 tty: Some(true),
                 cmd: None,
                 image: Some(TEST_IMAGE.to_string()),
@@ -78,13 +81,13 @@ pub(super) async fn run_container(
                     if with_network {
                         network_id = Some(create_network(extra_labels.clone()).await);
                         Some(NetworkingConfig {
-                            endpoints_config: HashMap::from([(
+                            endpoints_config: Some(HashMap::from([(
                                 "docker-reaper-test-network".to_string(),
                                 EndpointSettings {
                                     network_id: network_id.clone(),
                                     ..Default::default()
                                 },
-                            )]),
+                            )])),
                         })
                     } else {
                         None
@@ -96,7 +99,7 @@ pub(super) async fn run_container(
         .await
         .expect("failed to create container");
     client
-        .start_container::<&str>(&container_id, None)
+        .start_container(&container_id, None::<StartContainerOptions>)
         .await
         .unwrap_or_else(|e| panic!("failed to start container {container_id}: {e}"));
     RunContainerResult {
@@ -105,6 +108,67 @@ pub(super) async fn run_container(
     }
 }
 
+/// Stop a running container immediately.
+pub(super) async fn stop_container(id: &str) {
+    docker_client()
+        .stop_container(
+            id,
+            Some(StopContainerOptions {
+                t: Some(0),
+                ..Default::default()
+            }),
+        )
+        .await
+        .unwrap_or_else(|e| panic!("failed to stop container {id}: {e}"));
+}
+
+/// Create (but do not start) a container which mounts both the given named volume and an
+/// anonymous volume. Returns the ID of the container and the name of the anonymous volume.
+/// The label [TEST_LABEL] will always be set.
+pub(super) async fn create_container_with_volumes(named_volume: &str) -> (String, String) {
+    let client = docker_client();
+    // Running a container ensures that the test image is present
+    let RunContainerResult { container_id, .. } = run_container(false, None).await;
+    let image = client
+        .inspect_container(&container_id, None::<InspectContainerOptions>)
+        .await
+        .expect("failed to inspect container")
+        .image
+        .expect("missing container image");
+    let ContainerCreateResponse {
+        id: container_id, ..
+    } = client
+        .create_container(
+            None::<CreateContainerOptions>,
+            ContainerCreateBody {
+                image: Some(image),
+                labels: Some(HashMap::from([(
+                    TEST_LABEL.to_string(),
+                    "true".to_string(),
+                )])),
+                volumes: Some(HashMap::from([("/anonymous".to_string(), HashMap::new())])),
+                host_config: Some(HostConfig {
+                    binds: Some(vec![format!("{named_volume}:/named")]),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+        )
+        .await
+        .expect("failed to create container");
+    let anonymous_volume = client
+        .inspect_container(&container_id, None::<InspectContainerOptions>)
+        .await
+        .expect("failed to inspect container")
+        .mounts
+        .unwrap_or_default()
+        .into_iter()
+        .find(|mount| mount.destination.as_deref() == Some("/anonymous"))
+        .and_then(|mount| mount.name)
+        .expect("missing anonymous volume");
+    (container_id, anonymous_volume)
+}
+
 /// Create a network on the local Docker daemon. Returns the name of the created network.
 /// The label [TEST_LABEL] will always be set. Additional labels may also be specified.
 pub(super) async fn create_network(extra_labels: Option<HashMap<String, String>>) -> String {
@@ -115,9 +179,9 @@ pub(super) async fn create_network(extra_labels: Option<HashMap<String, String>>
     }
     let name = Utc::now().timestamp_millis().to_string(); // network names must be unique
     client
-        .create_network(CreateNetworkOptions {
+        .create_network(NetworkCreateRequest {
             name: name.clone(),
-            labels,
+            labels: Some(labels),
             ..Default::default()
         })
         .await
@@ -138,9 +202,9 @@ pub(super) async fn create_volume(extra_labels: Option<HashMap<String, String>>)
     }
     let name = Utc::now().timestamp_millis().to_string(); // volume names must be unique
     client
-        .create_volume(CreateVolumeOptions {
-            name: name.clone(),
-            labels,
+        .create_volume(VolumeCreateOptions {
+            name: Some(name.clone()),
+            labels: Some(labels),
             ..Default::default()
         })
         .await
@@ -148,10 +212,61 @@ pub(super) async fn create_volume(extra_labels: Option<HashMap<String, String>>)
     name
 }
 
+/// Create an image on the local Docker daemon by committing a test container. Returns the ID of
+/// the created image.
+/// The label [TEST_LABEL] will always be set. Additional labels may also be specified.
+pub(super) async fn create_image(extra_labels: Option<HashMap<String, String>>) -> String {
+    let client = docker_client();
+    let mut labels = HashMap::from([(TEST_LABEL.to_string(), "true".to_string())]);
+    if let Some(extra_labels) = extra_labels {
+        labels.extend(extra_labels)
+    }
+    let label_instruction = labels
+        .iter()
+        .map(|(key, value)| format!("{key}={value}"))
+        .collect::<Vec<_>>()
+        .join(" ");
+    let RunContainerResult { container_id, .. } = run_container(false, None).await;
+    let tag = Utc::now().timestamp_millis().to_string(); // image tags must be unique
+    client
+        .commit_container(
+            CommitContainerOptions {
+                container: Some(container_id.clone()),
+                repo: Some(TEST_LABEL.to_string()),
+                tag: Some(tag.clone()),
+                changes: Some(format!("LABEL {label_instruction}")),
+                ..Default::default()
+            },
+            ContainerConfig::default(),
+        )
+        .await
+        .expect("failed to commit container");
+    // Remove the source container so that the image is not considered in use.
+    client
+        .remove_container(
+            &container_id,
+            Some(RemoveContainerOptions {
+                force: true,
+                ..Default::default()
+            }),
+        )
+        .await
+        .expect("failed to remove container");
+    client
+        .inspect_image(&format!("{TEST_LABEL}:{tag}"))
+        .await
+        .expect("failed to inspect image")
+        .id
+        .expect("image ID not present")
+}
+
 /// Check whether a container with the given ID exists.
 pub(super) async fn container_exists(id: &str) -> bool {
     let client = docker_client();
-    match client.inspect_container(id, None).await {
+    match client
+        .inspect_container(id, None::<InspectContainerOptions>)
+        .await
+    {
         Ok(_) => return true,
         Err(err) => match err {
             bollard::errors::Error::DockerResponseServerError {
@@ -165,7 +280,10 @@ pub(super) async fn container_exists(id: &str) -> bool {
 /// Check whether a network with the given name exists.
 pub(super) async fn network_exists(name: &str) -> bool {
     let client = docker_client();
-    match client.inspect_network::<&str>(name, None).await {
+    match client
+        .inspect_network(name, None::<InspectNetworkOptions>)
+        .await
+    {
         Ok(_) => return true,
         Err(err) => match err {
             bollard::errors::Error::DockerResponseServerError {
@@ -190,6 +308,20 @@ pub(super) async fn volume_exists(name: &str) -> bool {
     }
 }
 
+/// Check whether an image with the given ID exists.
+pub(super) async fn image_exists(id: &str) -> bool {
+    let client = docker_client();
+    match client.inspect_image(id).await {
+        Ok(_) => true,
+        Err(err) => match err {
+            bollard::errors::Error::DockerResponseServerError {
+                status_code: 404, ..
+            } => false,
+            _ => panic!("unexpected error: {err}"),
+        },
+    }
+}
+
 /// Clean up all remaining test resources.
 pub(super) async fn cleanup() {
     let client = docker_client();
@@ -201,11 +333,27 @@ pub(super) async fn cleanup() {
             max_age: None,
             filters: &vec![Filter::new("label", TEST_LABEL)],
             reap_networks: true,
+            reap_volumes: Some(ReapVolumesMode::Anonymous),
+            ..Default::default()
         },
     )
     .await
     .expect("failed to clean up containers");
 
+    reap_images(
+        client,
+        &ReapImagesConfig {
+            dry_run: false,
+            min_age: None,
+            max_age: None,
+            filters: &vec![Filter::new("label", TEST_LABEL)],
+            force: true,
+            ..Default::default()
+        },
+    )
+    .await
+    .expect("failed to clean up images");
+
     reap_networks(
         client,
         &ReapNetworksConfig {
@@ -213,6 +361,7 @@ pub(super) async fn cleanup() {
             min_age: None,
             max_age: None,
             filters: &vec![Filter::new("label", TEST_LABEL)],
+            ..Default::default()
         },
     )
     .await
@@ -225,6 +374,7 @@ pub(super) async fn cleanup() {
             min_age: None,
             max_age: None,
             filters: &vec![Filter::new("label", TEST_LABEL)],
+            ..Default::default()
         },
     )
     .await
@@ -0,0 +1,111 @@
+//! Config file parsing tests.
+
+use crate::Commands;
+use crate::config::Config;
+use crate::reaper::BuildCacheAgeFrom;
+use tokio::time::Duration;
+
+/// Test that rules are parsed into the options of their respective subcommands.
+#[test]
+fn rules() {
+    let config: Config = toml::from_str(
+        r#"
+        [[rule]]
+        name = "challenges"
+        type = "containers"
+        every = "1m"
+        min_age = "30m"
+        filters = ["label=ctf", "status=exited"]
+        reap_networks = true
+
+        [[rule]]
+        name = "cache"
+        type = "build-cache"
+        age_from = "created"
+        "#,
+    )
+    .expect("failed to parse config");
+    assert_eq!(config.rules.len(), 2);
+    let challenges = &config.rules[0];
+    assert_eq!(challenges.name, "challenges");
+    assert_eq!(challenges.every, Some(Duration::from_secs(60)));
+    let Commands::Containers(ref args) = challenges.command else {
+        panic!("expected containers rule");
+    };
+    assert_eq!(args.min_age, Some(Duration::from_secs(30 * 60)));
+    assert_eq!(args.max_age, None);
+    assert_eq!(args.filters.len(), 2);
+    assert!(args.reap_networks);
+    let cache = &config.rules[1];
+    assert_eq!(cache.every, None);
+    let Commands::BuildCache(ref args) = cache.command else {
+        panic!("expected build-cache rule");
+    };
+    assert_eq!(args.age_from, BuildCacheAgeFrom::Created);
+}
+
+/// Test that misspelled or inapplicable rule options are rejected.
+#[test]
+fn unknown_option() {
+    let result = toml::from_str::<Config>(
+        r#"
+        [[rule]]
+        name = "challenges"
+        type = "volumes"
+        reap_networks = true
+        "#,
+    );
+    assert!(result.is_err());
+}
+
+/// Test that malformed durations and filters are rejected.
+#[test]
+fn invalid_values() {
+    for option in ["min_age = \"30\"", "filters = [\"label\"]"] {
+        let result = toml::from_str::<Config>(&format!(
+            "[[rule]]\nname = \"challenges\"\ntype = \"containers\"\n{option}\n"
+        ));
+        assert!(result.is_err(), "accepted {option}");
+    }
+}
+
+/// Test that rules with `idle_for` are rejected unless they are run repeatedly.
+#[test]
+fn idle_for_requires_every() {
+    let config: Config = toml::from_str(
+        r#"
+        [[rule]]
+        name = "idle"
+        type = "containers"
+        idle_for = "20m"
+        "#,
+    )
+    .expect("failed to parse config");
+    assert!(config.validate(None).is_err());
+    assert!(config.validate(Some(Duration::from_secs(60))).is_ok());
+}
+
+/// Test that options which require another option on the command line do so in rules as well.
+#[test]
+fn option_dependencies() {
+    for (rule_type, options, valid) in [
+        ("containers", "default_ttl = \"30m\"", false),
+        ("volumes", "default_ttl = \"30m\"", false),
+        (
+            "networks",
+            "default_ttl = \"30m\"\nttl_label = \"ttl\"",
+            true,
+        ),
+        ("containers", "keep_newest = 3", false),
+    ] {
+        let config: Config = toml::from_str(&format!(
+            "[[rule]]\nname = \"rule\"\ntype = \"{rule_type}\"\n{options}\n"
+        ))
+        .expect("failed to parse config");
+        assert_eq!(
+            config.validate(None).is_ok(),
+            valid,
+            "{rule_type}: {options}"
+        );
+    }
+}
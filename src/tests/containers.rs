@@ -5,12 +5,15 @@
 use std::collections::HashMap;
 
 use super::common::{
-    RunContainerResult, TEST_LABEL, cleanup, container_exists, docker_client, network_exists,
-    run_container,
+    RunContainerResult, TEST_LABEL, cleanup, container_exists, create_container_with_volumes,
+    create_volume, docker_client, network_exists, run_container, stop_container, volume_exists,
 };
+use crate::activity::{ActivityTracker, IdleCriteria};
 use crate::reaper::{
-    Filter, ReapContainersConfig, RemovalStatus, Resource, ResourceType, reap_containers,
+    ContainerAgeFrom, Filter, ProtectionLabel, ReapContainersConfig, ReapError, ReapVolumesMode,
+    RemovalLimit, RemovalStatus, Resource, ResourceType, container_expirations, reap_containers,
 };
+use chrono::{TimeDelta, Utc};
 use serial_test::serial;
 use tokio::time::{Duration, sleep};
 
@@ -35,6 +38,7 @@ async fn min_age() {
             max_age: None,
             filters: &vec![Filter::new("label", TEST_LABEL)],
             reap_networks: false,
+            ..Default::default()
         },
     )
     .await
@@ -65,6 +69,7 @@ async fn max_age() {
             max_age: Some(Duration::from_secs(2)),
             filters: &vec![Filter::new("label", TEST_LABEL)],
             reap_networks: false,
+            ..Default::default()
         },
     )
     .await
@@ -105,6 +110,7 @@ async fn filters() {
                 Filter::new("label", "color=orange"),
             ],
             reap_networks: false,
+            ..Default::default()
         },
     )
     .await
@@ -114,6 +120,42 @@ async fn filters() {
     cleanup().await;
 }
 
+/// Test that containers matching an exclusion filter are never reaped, even if they match the
+/// other filters.
+#[tokio::test]
+#[serial]
+async fn excludes() {
+    let RunContainerResult {
+        container_id: ref kept_container_id,
+        ..
+    } = run_container(
+        false,
+        Some(HashMap::from([("keep".to_string(), "true".to_string())])),
+    )
+    .await;
+    let RunContainerResult {
+        container_id: ref other_container_id,
+        ..
+    } = run_container(
+        false,
+        Some(HashMap::from([("keep".to_string(), "false".to_string())])),
+    )
+    .await;
+    reap_containers(
+        docker_client(),
+        &ReapContainersConfig {
+            filters: &vec![Filter::new("label", TEST_LABEL)],
+            excludes: &[Filter::new("label", "keep=true")],
+            ..Default::default()
+        },
+    )
+    .await
+    .expect("failed to reap containers");
+    assert!(container_exists(kept_container_id).await);
+    assert!(!container_exists(other_container_id).await);
+    cleanup().await;
+}
+
 /// Test that container-associated networks are also removed if `reap_networks` is set.
 #[tokio::test]
 #[serial]
@@ -130,6 +172,7 @@ async fn reap_networks() {
             max_age: None,
             filters: &vec![Filter::new("label", TEST_LABEL)],
             reap_networks: true,
+            ..Default::default()
         },
     )
     .await
@@ -142,6 +185,76 @@ async fn reap_networks() {
     cleanup().await;
 }
 
+/// Test that containers with the protection label, and their networks, are reported as protected
+/// but never removed.
+#[tokio::test]
+#[serial]
+async fn protection_label() {
+    let RunContainerResult {
+        container_id: protected_container_id,
+        network_id,
+    } = run_container(
+        true,
+        Some(HashMap::from([("pinned".to_string(), "yes".to_string())])),
+    )
+    .await;
+    let RunContainerResult {
+        container_id: other_container_id,
+        ..
+    } = run_container(false, None).await;
+    let removed_resources = reap_containers(
+        docker_client(),
+        &ReapContainersConfig {
+            filters: &vec![Filter::new("label", TEST_LABEL)],
+            reap_networks: true,
+            protection_label: &ProtectionLabel::new("pinned"),
+            ..Default::default()
+        },
+    )
+    .await
+    .expect("failed to reap containers");
+    assert!(
+        removed_resources
+            .iter()
+            .any(|resource| resource.id == protected_container_id
+                && matches!(resource.status, RemovalStatus::Protected))
+    );
+    assert!(container_exists(&protected_container_id).await);
+    assert!(network_exists(&network_id.expect("network ID not present")).await);
+    assert!(!container_exists(&other_container_id).await);
+    cleanup().await;
+}
+
+/// Test that nothing is removed if more containers match than `max_removals` allows.
+#[tokio::test]
+#[serial]
+async fn max_removals() {
+    let RunContainerResult {
+        container_id: first_container_id,
+        ..
+    } = run_container(false, None).await;
+    let RunContainerResult {
+        container_id: second_container_id,
+        ..
+    } = run_container(false, None).await;
+    let result = reap_containers(
+        docker_client(),
+        &ReapContainersConfig {
+            filters: &vec![Filter::new("label", TEST_LABEL)],
+            max_removals: Some(RemovalLimit::Count(1)),
+            ..Default::default()
+        },
+    )
+    .await;
+    let Err(ReapError::TooManyRemovals { resources, .. }) = result else {
+        panic!("expected run to be aborted, got {:?}", result);
+    };
+    assert_eq!(resources.len(), 2);
+    assert!(container_exists(&first_container_id).await);
+    assert!(container_exists(&second_container_id).await);
+    cleanup().await;
+}
+
 /// Test that resources are identified but not removed if `dry_run` is set.
 #[tokio::test]
 #[serial]
@@ -158,6 +271,7 @@ async fn dry_run() {
             max_age: None,
             filters: &vec![Filter::new("label", TEST_LABEL)],
             reap_networks: true,
+            ..Default::default()
         },
     )
     .await
@@ -166,13 +280,19 @@ async fn dry_run() {
         resource_type: ResourceType::Container,
         id: container_id.clone(),
         name: String::new(),
+        size: None,
+        age: None,
         status: RemovalStatus::Eligible,
+        reason: None,
     }));
     assert!(result.contains(&Resource {
         resource_type: ResourceType::Network,
         id: network_id.clone().expect("network ID not present"),
         name: String::new(),
+        size: None,
+        age: None,
         status: RemovalStatus::Eligible,
+        reason: None,
     }));
     assert_eq!(
         network_exists(&network_id.expect("network ID not present")).await,
@@ -181,3 +301,368 @@ async fn dry_run() {
     assert_eq!(container_exists(&container_id).await, true);
     cleanup().await;
 }
+
+/// Test that containers are reaped once older than the TTL read from their label, and that
+/// unlabeled containers fall back to `default_ttl`.
+#[tokio::test]
+#[serial]
+async fn ttl_label() {
+    let RunContainerResult {
+        container_id: ref short_ttl_container_id,
+        ..
+    } = run_container(
+        false,
+        Some(HashMap::from([("ttl".to_string(), "1s".to_string())])),
+    )
+    .await;
+    let RunContainerResult {
+        container_id: ref long_ttl_container_id,
+        ..
+    } = run_container(
+        false,
+        Some(HashMap::from([("ttl".to_string(), "1h".to_string())])),
+    )
+    .await;
+    let RunContainerResult {
+        container_id: ref unlabeled_container_id,
+        ..
+    } = run_container(false, None).await;
+    sleep(Duration::from_secs(2)).await;
+    reap_containers(
+        docker_client(),
+        &ReapContainersConfig {
+            filters: &vec![Filter::new("label", TEST_LABEL)],
+            ttl_label: Some("ttl"),
+            default_ttl: Some(Duration::from_secs(1)),
+            ..Default::default()
+        },
+    )
+    .await
+    .expect("failed to reap containers");
+    assert!(!container_exists(short_ttl_container_id).await);
+    assert!(container_exists(long_ttl_container_id).await);
+    assert!(!container_exists(unlabeled_container_id).await);
+    cleanup().await;
+}
+
+/// Test that only containers whose expiration label lies in the past are reaped.
+#[tokio::test]
+#[serial]
+async fn expiry_label() {
+    let expired = (Utc::now() - TimeDelta::hours(1)).to_rfc3339();
+    let unexpired = (Utc::now() + TimeDelta::hours(1)).timestamp().to_string();
+    let RunContainerResult {
+        container_id: ref expired_container_id,
+        ..
+    } = run_container(
+        false,
+        Some(HashMap::from([("expires-at".to_string(), expired)])),
+    )
+    .await;
+    let RunContainerResult {
+        container_id: ref unexpired_container_id,
+        ..
+    } = run_container(
+        false,
+        Some(HashMap::from([("expires-at".to_string(), unexpired)])),
+    )
+    .await;
+    let RunContainerResult {
+        container_id: ref malformed_container_id,
+        ..
+    } = run_container(
+        false,
+        Some(HashMap::from([(
+            "expires-at".to_string(),
+            "tomorrow".to_string(),
+        )])),
+    )
+    .await;
+    let RunContainerResult {
+        container_id: ref unlabeled_container_id,
+        ..
+    } = run_container(false, None).await;
+    reap_containers(
+        docker_client(),
+        &ReapContainersConfig {
+            filters: &vec![Filter::new("label", TEST_LABEL)],
+            expiry_label: Some("expires-at"),
+            ..Default::default()
+        },
+    )
+    .await
+    .expect("failed to reap containers");
+    assert!(!container_exists(expired_container_id).await);
+    assert!(container_exists(unexpired_container_id).await);
+    assert!(container_exists(malformed_container_id).await);
+    assert!(container_exists(unlabeled_container_id).await);
+    cleanup().await;
+}
+
+/// Test that pending expirations are reported for containers with a finite lifetime only.
+#[tokio::test]
+#[serial]
+async fn expirations() {
+    let expires_at = Utc::now() + TimeDelta::hours(1);
+    let RunContainerResult {
+        container_id: ref labeled_container_id,
+        ..
+    } = run_container(
+        false,
+        Some(HashMap::from([(
+            "expires-at".to_string(),
+            expires_at.timestamp().to_string(),
+        )])),
+    )
+    .await;
+    let RunContainerResult {
+        container_id: ref unlabeled_container_id,
+        ..
+    } = run_container(false, None).await;
+    let expirations = container_expirations(
+        docker_client(),
+        &ReapContainersConfig {
+            filters: &vec![Filter::new("label", TEST_LABEL)],
+            expiry_label: Some("expires-at"),
+            ..Default::default()
+        },
+    )
+    .await
+    .expect("failed to determine container expirations");
+    assert_eq!(expirations.len(), 1);
+    assert_eq!(&expirations[0].id, labeled_container_id);
+    assert_eq!(expirations[0].time.timestamp(), expires_at.timestamp());
+    assert!(container_exists(unlabeled_container_id).await);
+    cleanup().await;
+}
+
+/// Test that running containers are still reaped when they are stopped first, even if they ignore
+/// the stop signal.
+#[tokio::test]
+#[serial]
+async fn stop_timeout() {
+    // The shell runs as PID 1 and therefore ignores SIGTERM, so it must be killed after the
+    // timeout.
+    let RunContainerResult {
+        ref container_id, ..
+    } = run_container(false, None).await;
+    let removed_containers = reap_containers(
+        docker_client(),
+        &ReapContainersConfig {
+            filters: &vec![Filter::new("label", TEST_LABEL)],
+            stop_timeout: Some(Duration::from_secs(1)),
+            stop_signal: Some("SIGTERM"),
+            ..Default::default()
+        },
+    )
+    .await
+    .expect("failed to reap containers");
+    assert!(matches!(
+        removed_containers[0].status,
+        RemovalStatus::Success
+    ));
+    assert!(!container_exists(container_id).await);
+    cleanup().await;
+}
+
+/// Test that only anonymous volumes mounted by reaped containers are reaped by default.
+#[tokio::test]
+#[serial]
+async fn reap_anonymous_volumes() {
+    let named_volume = create_volume(None).await;
+    let (container_id, anonymous_volume) = create_container_with_volumes(&named_volume).await;
+    let removed_resources = reap_containers(
+        docker_client(),
+        &ReapContainersConfig {
+            filters: &vec![Filter::new("label", TEST_LABEL)],
+            reap_volumes: Some(ReapVolumesMode::Anonymous),
+            ..Default::default()
+        },
+    )
+    .await
+    .expect("failed to reap containers");
+    assert!(removed_resources.contains(&Resource {
+        resource_type: ResourceType::Volume,
+        id: anonymous_volume.clone(),
+        name: anonymous_volume.clone(),
+        size: None,
+        age: None,
+        status: RemovalStatus::Success,
+        reason: None,
+    }));
+    assert!(!container_exists(&container_id).await);
+    assert!(!volume_exists(&anonymous_volume).await);
+    assert!(volume_exists(&named_volume).await);
+    cleanup().await;
+}
+
+/// Test that all volumes mounted by reaped containers are reaped with `ReapVolumesMode::All`.
+#[tokio::test]
+#[serial]
+async fn reap_all_volumes() {
+    let named_volume = create_volume(None).await;
+    let (container_id, anonymous_volume) = create_container_with_volumes(&named_volume).await;
+    reap_containers(
+        docker_client(),
+        &ReapContainersConfig {
+            filters: &vec![Filter::new("label", TEST_LABEL)],
+            reap_volumes: Some(ReapVolumesMode::All),
+            ..Default::default()
+        },
+    )
+    .await
+    .expect("failed to reap containers");
+    assert!(!container_exists(&container_id).await);
+    assert!(!volume_exists(&anonymous_volume).await);
+    assert!(!volume_exists(&named_volume).await);
+    cleanup().await;
+}
+
+/// Test that only containers which exited more than `min_age` ago are reaped if their age is
+/// measured from when they finished.
+#[tokio::test]
+#[serial]
+async fn age_from_finished() {
+    let RunContainerResult {
+        container_id: ref exited_container_id,
+        ..
+    } = run_container(false, None).await;
+    let RunContainerResult {
+        container_id: ref running_container_id,
+        ..
+    } = run_container(false, None).await;
+    sleep(Duration::from_secs(2)).await;
+    stop_container(exited_container_id).await;
+    let removed_resources = reap_containers(
+        docker_client(),
+        &ReapContainersConfig {
+            min_age: Some(Duration::from_secs(2)),
+            age_from: ContainerAgeFrom::Finished,
+            filters: &vec![Filter::new("label", TEST_LABEL)],
+            ..Default::default()
+        },
+    )
+    .await
+    .expect("failed to reap containers");
+    assert!(removed_resources.is_empty());
+    sleep(Duration::from_secs(2)).await;
+    reap_containers(
+        docker_client(),
+        &ReapContainersConfig {
+            min_age: Some(Duration::from_secs(2)),
+            age_from: ContainerAgeFrom::Finished,
+            filters: &vec![Filter::new("label", TEST_LABEL)],
+            ..Default::default()
+        },
+    )
+    .await
+    .expect("failed to reap containers");
+    assert!(!container_exists(exited_container_id).await);
+    assert!(container_exists(running_container_id).await);
+    cleanup().await;
+}
+
+/// Test that containers which were never started are not reaped if their age is measured from
+/// when they started.
+#[tokio::test]
+#[serial]
+async fn age_from_started() {
+    let volume_name = create_volume(None).await;
+    let (created_container_id, _) = create_container_with_volumes(&volume_name).await;
+    let RunContainerResult {
+        container_id: ref started_container_id,
+        ..
+    } = run_container(false, None).await;
+    sleep(Duration::from_secs(2)).await;
+    reap_containers(
+        docker_client(),
+        &ReapContainersConfig {
+            min_age: Some(Duration::from_secs(1)),
+            age_from: ContainerAgeFrom::Started,
+            filters: &vec![Filter::new("label", TEST_LABEL)],
+            ..Default::default()
+        },
+    )
+    .await
+    .expect("failed to reap containers");
+    assert!(container_exists(&created_container_id).await);
+    assert!(!container_exists(started_container_id).await);
+    cleanup().await;
+}
+
+/// Test that containers are only reaped once they have been observed to be idle for `idle_for`.
+#[tokio::test]
+#[serial]
+async fn idle_for() {
+    let RunContainerResult { container_id, .. } = run_container(false, None).await;
+    let tracker = ActivityTracker::default();
+    let config = ReapContainersConfig {
+        filters: &vec![Filter::new("label", TEST_LABEL)],
+        idle: Some(IdleCriteria {
+            idle_for: Duration::from_secs(2),
+            cpu_threshold: 1.0,
+            network_threshold: 1024,
+            tracker: &tracker,
+        }),
+        ..Default::default()
+    };
+    // The first run only establishes a baseline
+    reap_containers(docker_client(), &config)
+        .await
+        .expect("failed to reap containers");
+    assert!(container_exists(&container_id).await);
+    sleep(Duration::from_secs(3)).await;
+    reap_containers(docker_client(), &config)
+        .await
+        .expect("failed to reap containers");
+    assert!(!container_exists(&container_id).await);
+    cleanup().await;
+}
+
+/// Test that only containers which are not among the `keep_newest` newest containers with the
+/// same `group_by_label` value are reaped.
+#[tokio::test]
+#[serial]
+async fn group_by_label() {
+    let team_a = HashMap::from([("team".to_string(), "a".to_string())]);
+    let team_b = HashMap::from([("team".to_string(), "b".to_string())]);
+    let RunContainerResult {
+        container_id: ref oldest_a_container_id,
+        ..
+    } = run_container(false, Some(team_a.clone())).await;
+    let RunContainerResult {
+        container_id: ref only_b_container_id,
+        ..
+    } = run_container(false, Some(team_b)).await;
+    sleep(Duration::from_secs(1)).await;
+    let RunContainerResult {
+        container_id: ref older_a_container_id,
+        ..
+    } = run_container(false, Some(team_a.clone())).await;
+    sleep(Duration::from_secs(1)).await;
+    let RunContainerResult {
+        container_id: ref newest_a_container_id,
+        ..
+    } = run_container(false, Some(team_a)).await;
+    let RunContainerResult {
+        container_id: ref unlabeled_container_id,
+        ..
+    } = run_container(false, None).await;
+    reap_containers(
+        docker_client(),
+        &ReapContainersConfig {
+            filters: &vec![Filter::new("label", TEST_LABEL)],
+            group_by_label: Some("team"),
+            keep_newest: Some(2),
+            ..Default::default()
+        },
+    )
+    .await
+    .expect("failed to reap containers");
+    assert!(!container_exists(oldest_a_container_id).await);
+    assert!(container_exists(older_a_container_id).await);
+    assert!(container_exists(newest_a_container_id).await);
+    assert!(container_exists(only_b_container_id).await);
+    assert!(container_exists(unlabeled_container_id).await);
+    cleanup().await;
+}
@@ -0,0 +1,106 @@
+//! Disk usage threshold tests.
+//!
+//! These are run serially because all test-related resources are cleaned up after each test.
+
+use super::common::{
+    RunContainerResult, TEST_LABEL, cleanup, container_exists, create_volume, docker_client,
+    run_container, volume_exists,
+};
+use crate::activity::ActivityTracker;
+use crate::disk::{DiskGate, DiskThreshold, reap_until_relieved, remove_until_relieved};
+use crate::reaper::{Filter, RemovalStatus, Resource, ResourceType};
+use crate::throttle::Throttle;
+use crate::{Commands, ContainersArgs, VolumesArgs};
+use serial_test::serial;
+
+fn containers_command() -> Commands {
+    Commands::Containers(ContainersArgs {
+        filters: vec![Filter::new("label", TEST_LABEL)],
+        ..Default::default()
+    })
+}
+
+/// Test that nothing is reaped while the disk usage threshold is not exceeded.
+#[tokio::test]
+#[serial]
+async fn within_threshold() {
+    let RunContainerResult { container_id, .. } = run_container(false, None).await;
+    let gate = DiskGate::new(docker_client(), DiskThreshold::FreeSpaceBelow(0))
+        .await
+        .expect("failed to determine disk usage");
+    let removed_resources = reap_until_relieved(
+        docker_client(),
+        &containers_command(),
+        false,
+        &ActivityTracker::default(),
+        &Throttle::default(),
+        &gate,
+    )
+    .await
+    .expect("failed to reap containers");
+    assert!(removed_resources.is_empty());
+    assert!(container_exists(&container_id).await);
+    cleanup().await;
+}
+
+/// Test that matching resources are reaped while the disk usage threshold is exceeded.
+#[tokio::test]
+#[serial]
+async fn exceeded_threshold() {
+    let RunContainerResult { container_id, .. } = run_container(false, None).await;
+    let gate = DiskGate::new(docker_client(), DiskThreshold::UsageAbove(0.0))
+        .await
+        .expect("failed to determine disk usage");
+    reap_until_relieved(
+        docker_client(),
+        &containers_command(),
+        false,
+        &ActivityTracker::default(),
+        &Throttle::default(),
+        &gate,
+    )
+    .await
+    .expect("failed to reap containers");
+    assert!(!container_exists(&container_id).await);
+    cleanup().await;
+}
+
+/// Test that resources of unknown age are no longer removed once the threshold is no longer
+/// exceeded.
+#[tokio::test]
+#[serial]
+async fn relieved_unaged() {
+    let first_volume_id = create_volume(None).await;
+    let second_volume_id = create_volume(None).await;
+    let resources = [&first_volume_id, &second_volume_id]
+        .into_iter()
+        .map(|id| Resource {
+            resource_type: ResourceType::Volume,
+            id: id.clone(),
+            name: id.clone(),
+            size: None,
+            age: None,
+            status: RemovalStatus::Eligible,
+            reason: None,
+        })
+        .collect();
+    let command = Commands::Volumes(VolumesArgs::default());
+    let throttle = Throttle::default();
+    let mut checks = 0;
+    let removed_resources = remove_until_relieved(
+        docker_client(),
+        &command,
+        &command.removal_options(&throttle),
+        resources,
+        || {
+            checks += 1;
+            Ok(checks == 1)
+        },
+    )
+    .await
+    .expect("failed to remove volumes");
+    assert_eq!(removed_resources.len(), 1);
+    assert!(!volume_exists(&first_volume_id).await);
+    assert!(volume_exists(&second_volume_id).await);
+    cleanup().await;
+}
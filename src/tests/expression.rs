@@ -0,0 +1,81 @@
+//! Match expression parsing and evaluation tests.
+
+use std::collections::HashMap;
+
+use crate::expression::MatchExpression;
+use bollard::models::Volume;
+use chrono::{TimeDelta, Utc};
+
+/// Create a volume summary as returned when listing volumes, without creating the volume itself.
+fn volume(name: &str, labels: &[(&str, &str)], age: TimeDelta) -> Volume {
+    Volume {
+        name: name.to_string(),
+        labels: labels
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect::<HashMap<_, _>>(),
+        created_at: Some((Utc::now() - age).to_rfc3339()),
+        ..Default::default()
+    }
+}
+
+/// Test that `and` binds more tightly than `or`, and that parentheses and `not` are respected.
+#[test]
+fn precedence() {
+    let expression: MatchExpression =
+        "label:team=* and (name:web-* or age>2h) and not label:pinned"
+            .parse()
+            .expect("failed to parse expression");
+    let new_web = volume("web-1", &[("team", "red")], TimeDelta::minutes(5));
+    let old_db = volume("db", &[("team", "blue")], TimeDelta::hours(3));
+    let new_db = volume("db", &[("team", "blue")], TimeDelta::minutes(5));
+    let pinned_web = volume(
+        "web-2",
+        &[("team", "red"), ("pinned", "")],
+        TimeDelta::hours(3),
+    );
+    let unowned_web = volume("web-3", &[], TimeDelta::hours(3));
+    assert!(expression.matches(&new_web));
+    assert!(expression.matches(&old_db));
+    assert!(!expression.matches(&new_db));
+    assert!(!expression.matches(&pinned_web));
+    assert!(!expression.matches(&unowned_web));
+
+    let expression: MatchExpression = "name:db or name:web-1 and label:pinned"
+        .parse()
+        .expect("failed to parse expression");
+    assert!(expression.matches(&new_db));
+    assert!(!expression.matches(&new_web));
+}
+
+/// Test that characters other than `*` and `?` in glob patterns only match themselves.
+#[test]
+fn glob_literals() {
+    let expression: MatchExpression = "label:range=<a.b>"
+        .parse()
+        .expect("failed to parse expression");
+    assert!(expression.matches(&volume("a", &[("range", "<a.b>")], TimeDelta::zero())));
+    assert!(!expression.matches(&volume("b", &[("range", "<a-b>")], TimeDelta::zero())));
+    assert!(!expression.matches(&volume("c", &[("range", "a.b")], TimeDelta::zero())));
+}
+
+/// Test that malformed expressions are rejected.
+#[test]
+fn invalid() {
+    for input in [
+        "",
+        "label:team and",
+        "(label:team",
+        "label:team)",
+        "label:team label:pinned",
+        "colour:red",
+        "label:",
+        "age=2h",
+        "age>soon",
+    ] {
+        assert!(
+            input.parse::<MatchExpression>().is_err(),
+            "expected {input:?} to be rejected"
+        );
+    }
+}
@@ -0,0 +1,140 @@
+//! Image reaping tests.
+//!
+//! These are run serially because all test-related resources are cleaned up after each test.
+
+use std::collections::HashMap;
+
+use super::common::{TEST_LABEL, cleanup, create_image, docker_client, image_exists};
+use crate::reaper::{Filter, ReapImagesConfig, RemovalStatus, Resource, ResourceType, reap_images};
+use bollard::models::ContainerCreateBody;
+use bollard::query_parameters::CreateContainerOptions;
+use serial_test::serial;
+use tokio::time::{Duration, sleep};
+
+/// Test that only images older than the `min_age` threshold are reaped.
+#[tokio::test]
+#[serial]
+async fn min_age() {
+    let old_image_id = create_image(None).await;
+    sleep(Duration::from_secs(2)).await;
+    let new_image_id = create_image(None).await;
+    reap_images(
+        docker_client(),
+        &ReapImagesConfig {
+            dry_run: false,
+            min_age: Some(Duration::from_secs(2)),
+            max_age: None,
+            filters: &vec![Filter::new("label", TEST_LABEL)],
+            force: true,
+            ..Default::default()
+        },
+    )
+    .await
+    .expect("failed to reap images");
+    assert!(!image_exists(&old_image_id).await);
+    assert!(image_exists(&new_image_id).await);
+    cleanup().await;
+}
+
+/// Test that only images matching the specified filters are reaped.
+#[tokio::test]
+#[serial]
+async fn filters() {
+    let purple_image_id = create_image(Some(HashMap::from([(
+        "color".to_string(),
+        "purple".to_string(),
+    )])))
+    .await;
+    let orange_image_id = create_image(Some(HashMap::from([(
+        "color".to_string(),
+        "orange".to_string(),
+    )])))
+    .await;
+    reap_images(
+        docker_client(),
+        &ReapImagesConfig {
+            dry_run: false,
+            min_age: None,
+            max_age: None,
+            filters: &vec![
+                Filter::new("label", TEST_LABEL),
+                Filter::new("label", "color=orange"),
+            ],
+            force: true,
+            ..Default::default()
+        },
+    )
+    .await
+    .expect("failed to reap images");
+    assert!(image_exists(&purple_image_id).await);
+    assert!(!image_exists(&orange_image_id).await);
+    cleanup().await;
+}
+
+/// Test that images used by an existing container are not reaped.
+#[tokio::test]
+#[serial]
+async fn in_use() {
+    let image_id = create_image(None).await;
+    docker_client()
+        .create_container(
+            None::<CreateContainerOptions>,
+            ContainerCreateBody {
+                image: Some(image_id.clone()),
+                labels: Some(HashMap::from([(
+                    TEST_LABEL.to_string(),
+                    "true".to_string(),
+                )])),
+                ..Default::default()
+            },
+        )
+        .await
+        .expect("failed to create container");
+    let result = reap_images(
+        docker_client(),
+        &ReapImagesConfig {
+            dry_run: false,
+            min_age: None,
+            max_age: None,
+            filters: &vec![Filter::new("label", TEST_LABEL)],
+            force: true,
+            ..Default::default()
+        },
+    )
+    .await
+    .expect("failed to reap images");
+    assert!(!result.iter().any(|resource| resource.id == image_id));
+    assert!(image_exists(&image_id).await);
+    cleanup().await;
+}
+
+/// Test that resources are identified but not removed if `dry_run` is set.
+#[tokio::test]
+#[serial]
+async fn dry_run() {
+    let image_id = create_image(None).await;
+    let result = reap_images(
+        docker_client(),
+        &ReapImagesConfig {
+            dry_run: true,
+            min_age: None,
+            max_age: None,
+            filters: &vec![Filter::new("label", TEST_LABEL)],
+            force: true,
+            ..Default::default()
+        },
+    )
+    .await
+    .expect("failed to reap images");
+    assert!(result.contains(&Resource {
+        resource_type: ResourceType::Image,
+        id: image_id.clone(),
+        name: String::new(),
+        size: None,
+        age: None,
+        status: RemovalStatus::Eligible,
+        reason: None,
+    }));
+    assert!(image_exists(&image_id).await);
+    cleanup().await;
+}
@@ -1,4 +1,12 @@
+mod build_cache;
 mod common;
+mod config;
 mod containers;
+mod disk;
+mod expression;
+mod images;
 mod networks;
+mod output;
+mod throttle;
 mod volumes;
+mod watch;
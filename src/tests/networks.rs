@@ -4,10 +4,14 @@
 
 use std::collections::HashMap;
 
-use super::common::{TEST_LABEL, cleanup, create_network, docker_client, network_exists};
+use super::common::{
+    RunContainerResult, TEST_LABEL, cleanup, create_network, docker_client, network_exists,
+    run_container,
+};
 use crate::reaper::{
     Filter, ReapNetworksConfig, RemovalStatus, Resource, ResourceType, reap_networks,
 };
+use chrono::{TimeDelta, Utc};
 use serial_test::serial;
 use tokio::time::{Duration, sleep};
 
@@ -25,6 +29,7 @@ async fn min_age() {
             min_age: Some(Duration::from_secs(2)),
             max_age: None,
             filters: &vec![Filter::new("label", TEST_LABEL)],
+            ..Default::default()
         },
     )
     .await
@@ -48,6 +53,7 @@ async fn max_age() {
             min_age: None,
             max_age: Some(Duration::from_secs(2)),
             filters: &vec![Filter::new("label", TEST_LABEL)],
+            ..Default::default()
         },
     )
     .await
@@ -81,6 +87,7 @@ async fn filters() {
                 Filter::new("label", TEST_LABEL),
                 Filter::new("label", "color=orange"),
             ],
+            ..Default::default()
         },
     )
     .await
@@ -102,6 +109,7 @@ async fn dry_run() {
             min_age: None,
             max_age: None,
             filters: &vec![Filter::new("label", TEST_LABEL)],
+            ..Default::default()
         },
     )
     .await
@@ -110,8 +118,136 @@ async fn dry_run() {
         resource_type: ResourceType::Network,
         id: network_id.clone(),
         name: String::new(),
-        status: RemovalStatus::Eligible
+        size: None,
+        age: None,
+        status: RemovalStatus::Eligible,
+        reason: None,
     }));
     assert_eq!(network_exists(&network_id).await, true);
     cleanup().await;
 }
+
+/// Test that networks are reaped once older than the TTL read from their label, and that
+/// unlabeled networks are not reaped without a `default_ttl`.
+#[tokio::test]
+#[serial]
+async fn ttl_label() {
+    let short_ttl_network_id =
+        create_network(Some(HashMap::from([("ttl".to_string(), "1s".to_string())]))).await;
+    let long_ttl_network_id =
+        create_network(Some(HashMap::from([("ttl".to_string(), "1h".to_string())]))).await;
+    let unlabeled_network_id = create_network(None).await;
+    sleep(Duration::from_secs(2)).await;
+    reap_networks(
+        docker_client(),
+        &ReapNetworksConfig {
+            filters: &vec![Filter::new("label", TEST_LABEL)],
+            ttl_label: Some("ttl"),
+            ..Default::default()
+        },
+    )
+    .await
+    .expect("failed to reap networks");
+    assert!(!network_exists(&short_ttl_network_id).await);
+    assert!(network_exists(&long_ttl_network_id).await);
+    assert!(network_exists(&unlabeled_network_id).await);
+    cleanup().await;
+}
+
+/// Test that only networks whose expiration label lies in the past are reaped.
+#[tokio::test]
+#[serial]
+async fn expiry_label() {
+    let expired = (Utc::now() - TimeDelta::hours(1)).to_rfc3339();
+    let unexpired = (Utc::now() + TimeDelta::hours(1)).timestamp().to_string();
+    let expired_network_id =
+        create_network(Some(HashMap::from([("expires-at".to_string(), expired)]))).await;
+    let unexpired_network_id =
+        create_network(Some(HashMap::from([("expires-at".to_string(), unexpired)]))).await;
+    reap_networks(
+        docker_client(),
+        &ReapNetworksConfig {
+            filters: &vec![Filter::new("label", TEST_LABEL)],
+            expiry_label: Some("expires-at"),
+            ..Default::default()
+        },
+    )
+    .await
+    .expect("failed to reap networks");
+    assert!(!network_exists(&expired_network_id).await);
+    assert!(network_exists(&unexpired_network_id).await);
+    cleanup().await;
+}
+
+/// Test that predefined networks are never reaped.
+#[tokio::test]
+#[serial]
+async fn predefined() {
+    let removed_networks = reap_networks(
+        docker_client(),
+        &ReapNetworksConfig {
+            filters: &vec![Filter::new("name", "^bridge$")],
+            ..Default::default()
+        },
+    )
+    .await
+    .expect("failed to reap networks");
+    assert_eq!(removed_networks.len(), 1);
+    assert!(matches!(
+        removed_networks[0].status,
+        RemovalStatus::Protected
+    ));
+    assert!(network_exists("bridge").await);
+    cleanup().await;
+}
+
+/// Test that networks in `protected_networks` are not reaped.
+#[tokio::test]
+#[serial]
+async fn protected_networks() {
+    let protected_network_id = create_network(None).await;
+    let unprotected_network_id = create_network(None).await;
+    reap_networks(
+        docker_client(),
+        &ReapNetworksConfig {
+            filters: &vec![Filter::new("label", TEST_LABEL)],
+            protected_networks: std::slice::from_ref(&protected_network_id),
+            ..Default::default()
+        },
+    )
+    .await
+    .expect("failed to reap networks");
+    assert!(network_exists(&protected_network_id).await);
+    assert!(!network_exists(&unprotected_network_id).await);
+    cleanup().await;
+}
+
+/// Test that networks with connected containers are skipped when `unused_only` is set.
+#[tokio::test]
+#[serial]
+async fn unused_only() {
+    let RunContainerResult {
+        network_id: used_network_id,
+        ..
+    } = run_container(true, None).await;
+    let used_network_id = used_network_id.expect("missing network ID");
+    let unused_network_id = create_network(None).await;
+    let removed_networks = reap_networks(
+        docker_client(),
+        &ReapNetworksConfig {
+            filters: &vec![Filter::new("label", TEST_LABEL)],
+            unused_only: true,
+            ..Default::default()
+        },
+    )
+    .await
+    .expect("failed to reap networks");
+    let used_network = removed_networks
+        .iter()
+        .find(|network| network.id == used_network_id)
+        .expect("missing used network");
+    assert!(matches!(used_network.status, RemovalStatus::InUse));
+    assert!(network_exists(&used_network_id).await);
+    assert!(!network_exists(&unused_network_id).await);
+    cleanup().await;
+}
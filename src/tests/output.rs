@@ -0,0 +1,124 @@
+//! Report output format tests.
+
+use crate::output::{Outcome, OutputFormat, Reporter};
+use crate::reaper::{ReapError, RemovalError, RemovalStatus, Resource, ResourceType};
+use tokio::time::Duration;
+
+fn resources() -> Vec<Resource> {
+    vec![
+        Resource {
+            resource_type: ResourceType::Container,
+            id: "abc123".to_string(),
+            name: "/web".to_string(),
+            size: None,
+            age: Some(Duration::from_secs(90)),
+            status: RemovalStatus::Success,
+            reason: None,
+        },
+        Resource {
+            resource_type: ResourceType::Volume,
+            id: "data".to_string(),
+            name: "data".to_string(),
+            size: None,
+            age: None,
+            status: RemovalStatus::Error(RemovalError::Docker(
+                bollard::errors::Error::DockerResponseServerError {
+                    status_code: 409,
+                    message: "volume is in use".to_string(),
+                },
+            )),
+            reason: None,
+        },
+    ]
+}
+
+fn write(reporter: &mut Reporter, rule: Option<&str>) -> String {
+    let mut output = Vec::new();
+    reporter
+        .write(&mut output, &resources(), rule)
+        .expect("failed to write report");
+    String::from_utf8(output).expect("report is not valid UTF-8")
+}
+
+/// Test that JSON reports contain an array with one object per resource.
+#[test]
+fn json() {
+    let output = write(
+        &mut Reporter::new(OutputFormat::Json, false),
+        Some("cleanup"),
+    );
+    let value: serde_json::Value = serde_json::from_str(&output).expect("invalid JSON");
+    let records = value.as_array().expect("expected JSON array");
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[0]["rule"], "cleanup");
+    assert_eq!(records[0]["type"], "container");
+    assert_eq!(records[0]["id"], "abc123");
+    assert_eq!(records[0]["age_seconds"], 90);
+    assert_eq!(records[0]["status"], "removed");
+    assert!(records[0]["error"].is_null());
+    assert_eq!(records[1]["status"], "error");
+    let error = records[1]["error"]
+        .as_str()
+        .expect("expected error message");
+    assert!(error.contains("volume is in use"));
+}
+
+/// Test that NDJSON reports contain one JSON object per line.
+#[test]
+fn ndjson() {
+    let output = write(&mut Reporter::new(OutputFormat::Ndjson, false), None);
+    let lines: Vec<&str> = output.lines().collect();
+    assert_eq!(lines.len(), 2);
+    for line in lines {
+        let value: serde_json::Value = serde_json::from_str(line).expect("invalid JSON");
+        assert!(value.is_object());
+        assert!(value["rule"].is_null());
+    }
+}
+
+/// Test that the CSV header row is only written once across runs.
+#[test]
+fn csv() {
+    let mut reporter = Reporter::new(OutputFormat::Csv, false);
+    let first = write(&mut reporter, None);
+    let second = write(&mut reporter, None);
+    assert_eq!(
+        first.lines().next(),
+        Some("rule,type,id,name,size,age_seconds,status,reason,error")
+    );
+    assert_eq!(first.lines().count(), 3);
+    assert_eq!(second.lines().count(), 2);
+    assert_eq!(
+        second.lines().next(),
+        Some(",container,abc123,/web,,90,removed,,")
+    );
+}
+
+/// Test that runs are classified by whether any removal failed.
+#[test]
+fn run_outcome() {
+    let reporter = Reporter::new(OutputFormat::Table, false);
+    assert_eq!(reporter.run_outcome(&[]), Outcome::NothingMatched);
+    assert_eq!(reporter.run_outcome(&resources()), Outcome::PartialFailure);
+    let mut in_progress = resources();
+    in_progress.truncate(1);
+    in_progress[0].status = RemovalStatus::InProgress;
+    assert_eq!(reporter.run_outcome(&in_progress), Outcome::Success);
+    let strict_reporter = Reporter::new(OutputFormat::Table, true);
+    assert_eq!(
+        strict_reporter.run_outcome(&in_progress),
+        Outcome::PartialFailure
+    );
+}
+
+/// Test that the overall outcome is the most severe outcome of all runs.
+#[test]
+fn overall_outcome() {
+    let mut reporter = Reporter::new(OutputFormat::Table, false);
+    assert_eq!(reporter.outcome(), Outcome::NothingMatched);
+    reporter.report(Ok(Vec::new()), None, Duration::ZERO);
+    assert_eq!(reporter.outcome(), Outcome::NothingMatched);
+    reporter.report(Err(ReapError::InvalidAgeBound), None, Duration::ZERO);
+    reporter.report(Ok(Vec::new()), None, Duration::ZERO);
+    assert_eq!(reporter.outcome(), Outcome::Fatal);
+}
@@ -0,0 +1,43 @@
+//! Removal throttling tests.
+
+use crate::throttle::Throttle;
+use tokio::time::{Duration, Instant, timeout};
+
+/// Test that no more than `concurrency` permits are handed out at once.
+#[tokio::test]
+async fn concurrency() {
+    let throttle = Throttle::new(Some(1), None);
+    let permit = throttle.acquire().await;
+    assert!(permit.is_some());
+    assert!(
+        timeout(Duration::from_millis(50), throttle.acquire())
+            .await
+            .is_err()
+    );
+    drop(permit);
+    assert!(
+        timeout(Duration::from_millis(50), throttle.acquire())
+            .await
+            .is_ok()
+    );
+}
+
+/// Test that consecutive removals are spaced out according to `rate`.
+#[tokio::test]
+async fn rate() {
+    let throttle = Throttle::new(None, Some(20.0));
+    let started = Instant::now();
+    futures::future::join_all((0..3).map(|_| throttle.acquire())).await;
+    assert!(started.elapsed() >= Duration::from_millis(100));
+}
+
+/// Test that rates too low to be represented as an interval are raised rather than panicking.
+#[tokio::test]
+async fn min_rate() {
+    let throttle = Throttle::new(None, Some(1e-30));
+    assert!(
+        timeout(Duration::from_millis(50), throttle.acquire())
+            .await
+            .is_ok()
+    );
+}
@@ -4,10 +4,14 @@
 
 use std::collections::HashMap;
 
-use super::common::{TEST_LABEL, cleanup, create_volume, docker_client, volume_exists};
+use super::common::{
+    TEST_LABEL, cleanup, create_container_with_volumes, create_volume, docker_client, volume_exists,
+};
 use crate::reaper::{
-    Filter, ReapVolumesConfig, RemovalStatus, Resource, ResourceType, reap_volumes,
+    Filter, NamePattern, ProtectionLabel, ReapVolumesConfig, RemovalStatus, Resource, ResourceType,
+    reap_volumes,
 };
+use chrono::{TimeDelta, Utc};
 use serial_test::serial;
 use tokio::time::{Duration, sleep};
 
@@ -25,6 +29,7 @@ async fn min_age() {
             min_age: Some(Duration::from_secs(2)),
             max_age: None,
             filters: &vec![Filter::new("label", TEST_LABEL)],
+            ..Default::default()
         },
     )
     .await
@@ -48,6 +53,7 @@ async fn max_age() {
             min_age: None,
             max_age: Some(Duration::from_secs(2)),
             filters: &vec![Filter::new("label", TEST_LABEL)],
+            ..Default::default()
         },
     )
     .await
@@ -81,6 +87,7 @@ async fn filters() {
                 Filter::new("label", TEST_LABEL),
                 Filter::new("label", "color=orange"),
             ],
+            ..Default::default()
         },
     )
     .await
@@ -102,6 +109,7 @@ async fn dry_run() {
             min_age: None,
             max_age: None,
             filters: &vec![Filter::new("label", TEST_LABEL)],
+            ..Default::default()
         },
     )
     .await
@@ -110,8 +118,196 @@ async fn dry_run() {
         resource_type: ResourceType::Volume,
         id: volume_id.clone(),
         name: String::new(),
-        status: RemovalStatus::Eligible
+        size: None,
+        age: None,
+        status: RemovalStatus::Eligible,
+        reason: None,
     }));
     assert_eq!(volume_exists(&volume_id).await, true);
     cleanup().await;
 }
+
+/// Test that volumes are reaped once older than the TTL read from their label, and that
+/// unlabeled volumes are not reaped without a `default_ttl`.
+#[tokio::test]
+#[serial]
+async fn ttl_label() {
+    let short_ttl_volume_id =
+        create_volume(Some(HashMap::from([("ttl".to_string(), "1s".to_string())]))).await;
+    let long_ttl_volume_id =
+        create_volume(Some(HashMap::from([("ttl".to_string(), "1h".to_string())]))).await;
+    let unlabeled_volume_id = create_volume(None).await;
+    sleep(Duration::from_secs(2)).await;
+    reap_volumes(
+        docker_client(),
+        &ReapVolumesConfig {
+            filters: &vec![Filter::new("label", TEST_LABEL)],
+            ttl_label: Some("ttl"),
+            ..Default::default()
+        },
+    )
+    .await
+    .expect("failed to reap volumes");
+    assert!(!volume_exists(&short_ttl_volume_id).await);
+    assert!(volume_exists(&long_ttl_volume_id).await);
+    assert!(volume_exists(&unlabeled_volume_id).await);
+    cleanup().await;
+}
+
+/// Test that only volumes whose expiration label lies in the past are reaped.
+#[tokio::test]
+#[serial]
+async fn expiry_label() {
+    let expired = (Utc::now() - TimeDelta::hours(1)).to_rfc3339();
+    let unexpired = (Utc::now() + TimeDelta::hours(1)).timestamp().to_string();
+    let expired_volume_id =
+        create_volume(Some(HashMap::from([("expires-at".to_string(), expired)]))).await;
+    let unexpired_volume_id =
+        create_volume(Some(HashMap::from([("expires-at".to_string(), unexpired)]))).await;
+    reap_volumes(
+        docker_client(),
+        &ReapVolumesConfig {
+            filters: &vec![Filter::new("label", TEST_LABEL)],
+            expiry_label: Some("expires-at"),
+            ..Default::default()
+        },
+    )
+    .await
+    .expect("failed to reap volumes");
+    assert!(!volume_exists(&expired_volume_id).await);
+    assert!(volume_exists(&unexpired_volume_id).await);
+    cleanup().await;
+}
+
+/// Test that volumes mounted by a container are skipped as in use when `unused_only` is set.
+#[tokio::test]
+#[serial]
+async fn unused_only() {
+    let used_volume_id = create_volume(None).await;
+    let unused_volume_id = create_volume(None).await;
+    create_container_with_volumes(&used_volume_id).await;
+    let removed_resources = reap_volumes(
+        docker_client(),
+        &ReapVolumesConfig {
+            filters: &vec![Filter::new("label", TEST_LABEL)],
+            unused_only: true,
+            ..Default::default()
+        },
+    )
+    .await
+    .expect("failed to reap volumes");
+    assert!(
+        removed_resources
+            .iter()
+            .any(|resource| resource.id == used_volume_id
+                && matches!(resource.status, RemovalStatus::InUse))
+    );
+    assert!(volume_exists(&used_volume_id).await);
+    assert!(!volume_exists(&unused_volume_id).await);
+    cleanup().await;
+}
+
+/// Test that the oldest volumes are evicted regardless of their age if more than `max_count`
+/// volumes match, and that dry-run reports show why.
+#[tokio::test]
+#[serial]
+async fn max_count() {
+    let oldest_volume_id = create_volume(None).await;
+    sleep(Duration::from_secs(1)).await;
+    let older_volume_id = create_volume(None).await;
+    sleep(Duration::from_secs(1)).await;
+    let newest_volume_id = create_volume(None).await;
+    let filters = vec![Filter::new("label", TEST_LABEL)];
+    let eligible_resources = reap_volumes(
+        docker_client(),
+        &ReapVolumesConfig {
+            dry_run: true,
+            filters: &filters,
+            max_count: Some(1),
+            ..Default::default()
+        },
+    )
+    .await
+    .expect("failed to reap volumes");
+    assert_eq!(eligible_resources.len(), 2);
+    assert!(
+        eligible_resources
+            .iter()
+            .all(|resource| resource.reason.is_some())
+    );
+    reap_volumes(
+        docker_client(),
+        &ReapVolumesConfig {
+            filters: &filters,
+            max_count: Some(1),
+            ..Default::default()
+        },
+    )
+    .await
+    .expect("failed to reap volumes");
+    assert!(!volume_exists(&oldest_volume_id).await);
+    assert!(!volume_exists(&older_volume_id).await);
+    assert!(volume_exists(&newest_volume_id).await);
+    cleanup().await;
+}
+
+/// Test that protected volumes count towards `max_count` but are never evicted, even if they
+/// alone exceed it.
+#[tokio::test]
+#[serial]
+async fn max_count_protected() {
+    let protected_labels = HashMap::from([("pinned".to_string(), "yes".to_string())]);
+    let first_protected_volume_id = create_volume(Some(protected_labels.clone())).await;
+    sleep(Duration::from_secs(1)).await;
+    let second_protected_volume_id = create_volume(Some(protected_labels)).await;
+    sleep(Duration::from_secs(1)).await;
+    let unprotected_volume_id = create_volume(None).await;
+    let removed_resources = reap_volumes(
+        docker_client(),
+        &ReapVolumesConfig {
+            filters: &vec![Filter::new("label", TEST_LABEL)],
+            max_count: Some(1),
+            protection_label: &ProtectionLabel::new("pinned"),
+            ..Default::default()
+        },
+    )
+    .await
+    .expect("failed to reap volumes");
+    assert_eq!(removed_resources.len(), 1);
+    assert!(volume_exists(&first_protected_volume_id).await);
+    assert!(volume_exists(&second_protected_volume_id).await);
+    assert!(!volume_exists(&unprotected_volume_id).await);
+    cleanup().await;
+}
+
+/// Test that only volumes whose entire name matches one of the name patterns are reaped.
+#[tokio::test]
+#[serial]
+async fn name_patterns() {
+    let regex_volume_id = create_volume(None).await;
+    sleep(Duration::from_millis(10)).await;
+    let glob_volume_id = create_volume(None).await;
+    sleep(Duration::from_millis(10)).await;
+    let partial_volume_id = create_volume(None).await;
+    let name_regexes = vec![
+        NamePattern::regex(&regex_volume_id).expect("invalid regex"),
+        // Matches only part of the name, which is not enough
+        NamePattern::regex(&partial_volume_id[1..]).expect("invalid regex"),
+    ];
+    let name_globs = vec![NamePattern::glob(&glob_volume_id).expect("invalid glob")];
+    reap_volumes(
+        docker_client(),
+        &ReapVolumesConfig {
+            filters: &vec![Filter::new("label", TEST_LABEL)],
+            name_regexes: &name_regexes,
+            name_globs: &name_globs,
+            ..Default::default()
+        },
+    )
+    .await
+    .expect("failed to reap volumes");
+    assert!(!volume_exists(&regex_volume_id).await);
+    assert!(!volume_exists(&glob_volume_id).await);
+    assert!(volume_exists(&partial_volume_id).await);
+    cleanup().await;
+}
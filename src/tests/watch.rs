@@ -0,0 +1,95 @@
+//! Watch mode scheduling tests.
+
+use crate::reaper::Expiration;
+use crate::watch::Schedule;
+use chrono::{TimeDelta, Utc};
+use tokio::time::{Duration, Instant};
+
+const RESYNC_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Create an expiration of the resource with the given ID, relative to the current time.
+fn expiration(id: &str, in_time: TimeDelta) -> Expiration {
+    Expiration {
+        id: id.to_string(),
+        time: Utc::now() + in_time,
+    }
+}
+
+/// Test that resyncs rebuild the pending expirations and are spaced out by the resync interval.
+#[test]
+fn resync() {
+    let mut schedule = Schedule::new(RESYNC_INTERVAL);
+    assert!(schedule.next_resync() <= Instant::now());
+    assert!(schedule.next_expiration().is_none());
+
+    schedule.resynced(Some(vec![
+        expiration("later", TimeDelta::minutes(10)),
+        expiration("sooner", TimeDelta::minutes(5)),
+    ]));
+    assert!(schedule.next_resync() > Instant::now() + RESYNC_INTERVAL / 2);
+    let (id, at) = schedule.next_expiration().expect("no pending expiration");
+    assert_eq!(id, "sooner");
+    assert!(at > Instant::now() + Duration::from_secs(4 * 60));
+
+    // Failing to determine expirations keeps the previous ones
+    schedule.resynced(None);
+    assert_eq!(
+        schedule.next_expiration().map(|(id, _)| id).as_deref(),
+        Some("sooner")
+    );
+
+    schedule.resynced(Some(vec![expiration("other", TimeDelta::minutes(1))]));
+    assert_eq!(
+        schedule.next_expiration().map(|(id, _)| id).as_deref(),
+        Some("other")
+    );
+}
+
+/// Test that expirations are replaced by events and removed once expired or destroyed.
+#[test]
+fn events() {
+    let mut schedule = Schedule::new(RESYNC_INTERVAL);
+    schedule.reschedule("a", vec![expiration("a", TimeDelta::minutes(5))]);
+    schedule.reschedule("b", vec![expiration("b", TimeDelta::minutes(10))]);
+    assert_eq!(
+        schedule.next_expiration().map(|(id, _)| id).as_deref(),
+        Some("a")
+    );
+
+    schedule.reschedule("a", vec![expiration("a", TimeDelta::minutes(15))]);
+    assert_eq!(
+        schedule.next_expiration().map(|(id, _)| id).as_deref(),
+        Some("b")
+    );
+
+    // A resource which no longer expires is unscheduled
+    schedule.reschedule("b", Vec::new());
+    assert_eq!(
+        schedule.next_expiration().map(|(id, _)| id).as_deref(),
+        Some("a")
+    );
+
+    schedule.remove("a");
+    assert!(schedule.next_expiration().is_none());
+}
+
+/// Test that losing the event stream delays resubscribing without affecting pending work, and
+/// that resubscribing triggers an immediate resync.
+#[test]
+fn resubscribe() {
+    let mut schedule = Schedule::new(RESYNC_INTERVAL);
+    schedule.resynced(Some(vec![expiration("a", TimeDelta::zero())]));
+    assert!(schedule.resubscribe_at().is_none());
+
+    schedule.event_stream_lost();
+    let resubscribe_at = schedule
+        .resubscribe_at()
+        .expect("resubscribe not scheduled");
+    assert!(resubscribe_at > Instant::now());
+    assert!(schedule.next_resync() > resubscribe_at);
+    assert!(schedule.next_expiration().is_some());
+
+    schedule.resubscribed();
+    assert!(schedule.resubscribe_at().is_none());
+    assert!(schedule.next_resync() <= Instant::now());
+}
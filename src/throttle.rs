@@ -0,0 +1,56 @@
+use std::sync::Mutex;
+use tokio::sync::{Semaphore, SemaphorePermit};
+use tokio::time::{Duration, Instant, sleep_until};
+
+/// Lowest supported rate of removals per second, i.e. one removal per day. Lower rates would make
+/// the interval between removals overflow.
+pub(crate) const MIN_RATE: f64 = 1.0 / 86_400.0;
+
+/// Limits how many removals are in flight at once and how often new ones are started, shared
+/// across all resource types so that busy hosts are not flooded with requests.
+#[derive(Debug, Default)]
+pub(crate) struct Throttle {
+    /// Permits for removals in flight, if their number is limited.
+    permits: Option<Semaphore>,
+    /// Minimum time between the start of consecutive removals, if their rate is limited.
+    interval: Option<Duration>,
+    /// Earliest time at which the next removal may start.
+    next_start: Mutex<Option<Instant>>,
+}
+
+impl Throttle {
+    /// Creates a throttle allowing at most `concurrency` removals in flight and starting at most
+    /// `rate` removals per second. Rates below [`MIN_RATE`] are raised to it.
+    pub(crate) fn new(concurrency: Option<usize>, rate: Option<f64>) -> Self {
+        Self {
+            permits: concurrency.map(Semaphore::new),
+            interval: rate.map(|rate| Duration::from_secs_f64(1.0 / rate.max(MIN_RATE))),
+            next_start: Mutex::new(None),
+        }
+    }
+
+    /// Waits until another removal may start. The returned permit must be held until the removal
+    /// has finished.
+    pub(crate) async fn acquire(&self) -> Option<SemaphorePermit<'_>> {
+        let permit = match &self.permits {
+            Some(permits) => Some(
+                permits
+                    .acquire()
+                    .await
+                    .expect("throttle semaphore is never closed"),
+            ),
+            None => None,
+        };
+        if let Some(interval) = self.interval {
+            // Reserve the next slot before waiting, so that concurrent removals are spaced out
+            let start = {
+                let mut next_start = self.next_start.lock().expect("throttle lock poisoned");
+                let start = next_start.map_or(Instant::now(), |next| next.max(Instant::now()));
+                *next_start = Some(start + interval);
+                start
+            };
+            sleep_until(start).await;
+        }
+        permit
+    }
+}
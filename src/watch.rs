@@ -0,0 +1,353 @@
+use bollard::Docker;
+use bollard::query_parameters::EventsOptions;
+use chrono::{DateTime, Utc};
+use futures::{Stream, StreamExt};
+use std::collections::HashMap;
+use std::pin::Pin;
+use tokio::time::{Duration, Instant, sleep_until};
+use tracing::{debug, info, warn};
+
+use crate::output::Reporter;
+use crate::reaper::{
+    Expiration, Filter, ReapContainersConfig, ReapError, ReapNetworksConfig, ReapVolumesConfig,
+    Resource, container_expirations, network_expirations, reap_containers, reap_networks,
+    reap_volumes, volume_expirations,
+};
+
+/// Interval between full resyncs in watch mode unless overridden with `--every`.
+pub(crate) const DEFAULT_RESYNC_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+/// Delay before resubscribing to the event stream after it failed or ended.
+const RESUBSCRIBE_DELAY: Duration = Duration::from_secs(5);
+
+/// Extra time to wait past each expiration. Docker reports creation times with a resolution of one
+/// second, so a resource may not yet be eligible at the exact computed instant.
+const EXPIRATION_GRACE: Duration = Duration::from_secs(1);
+
+/// A reaper configuration that can be driven by the Docker events stream.
+pub(crate) trait Watch {
+    /// Docker event type of the reaped resources.
+    const EVENT_TYPE: &'static str;
+    /// Docker Engine filter which selects a single resource by the actor ID of its events.
+    const ID_FILTER: &'static str;
+    /// Docker event actions after which the expiration of a resource is (re)determined.
+    const SCHEDULE_EVENTS: &'static [&'static str] = &["create"];
+
+    /// Reaps matching resources, optionally restricted to the resource with the given ID.
+    async fn reap(&self, docker: &Docker, only: Option<&str>) -> Result<Vec<Resource>, ReapError>;
+
+    /// Determines when matching resources will expire, optionally restricted to the resource with
+    /// the given ID.
+    async fn expirations(
+        &self,
+        docker: &Docker,
+        only: Option<&str>,
+    ) -> Result<Vec<Expiration>, ReapError>;
+}
+
+/// Returns the given filters, extended to select only the resource with the given ID (if any).
+fn restrict_filters(filters: &[Filter], id_filter: &str, only: Option<&str>) -> Vec<Filter> {
+    let mut filters = filters.to_vec();
+    if let Some(id) = only {
+        filters.push(Filter::new(id_filter, id));
+    }
+    filters
+}
+
+impl Watch for ReapContainersConfig<'_> {
+    const EVENT_TYPE: &'static str = "container";
+    const ID_FILTER: &'static str = "id";
+    // The age of containers may be measured from when they were last started or exited.
+    const SCHEDULE_EVENTS: &'static [&'static str] = &["create", "start", "die"];
+
+    async fn reap(&self, docker: &Docker, only: Option<&str>) -> Result<Vec<Resource>, ReapError> {
+        let filters = restrict_filters(self.filters, Self::ID_FILTER, only);
+        reap_containers(
+            docker,
+            &ReapContainersConfig {
+                filters: &filters,
+                ..*self
+            },
+        )
+        .await
+    }
+
+    async fn expirations(
+        &self,
+        docker: &Docker,
+        only: Option<&str>,
+    ) -> Result<Vec<Expiration>, ReapError> {
+        let filters = restrict_filters(self.filters, Self::ID_FILTER, only);
+        container_expirations(
+            docker,
+            &ReapContainersConfig {
+                filters: &filters,
+                ..*self
+            },
+        )
+        .await
+    }
+}
+
+impl Watch for ReapNetworksConfig<'_> {
+    const EVENT_TYPE: &'static str = "network";
+    const ID_FILTER: &'static str = "id";
+
+    async fn reap(&self, docker: &Docker, only: Option<&str>) -> Result<Vec<Resource>, ReapError> {
+        let filters = restrict_filters(self.filters, Self::ID_FILTER, only);
+        reap_networks(
+            docker,
+            &ReapNetworksConfig {
+                filters: &filters,
+                ..*self
+            },
+        )
+        .await
+    }
+
+    async fn expirations(
+        &self,
+        docker: &Docker,
+        only: Option<&str>,
+    ) -> Result<Vec<Expiration>, ReapError> {
+        let filters = restrict_filters(self.filters, Self::ID_FILTER, only);
+        network_expirations(
+            docker,
+            &ReapNetworksConfig {
+                filters: &filters,
+                ..*self
+            },
+        )
+        .await
+    }
+}
+
+impl Watch for ReapVolumesConfig<'_> {
+    const EVENT_TYPE: &'static str = "volume";
+    // Volume events use the volume name as the actor ID.
+    const ID_FILTER: &'static str = "name";
+
+    async fn reap(&self, docker: &Docker, only: Option<&str>) -> Result<Vec<Resource>, ReapError> {
+        let filters = restrict_filters(self.filters, Self::ID_FILTER, only);
+        reap_volumes(
+            docker,
+            &ReapVolumesConfig {
+                filters: &filters,
+                ..*self
+            },
+        )
+        .await
+    }
+
+    async fn expirations(
+        &self,
+        docker: &Docker,
+        only: Option<&str>,
+    ) -> Result<Vec<Expiration>, ReapError> {
+        let filters = restrict_filters(self.filters, Self::ID_FILTER, only);
+        volume_expirations(
+            docker,
+            &ReapVolumesConfig {
+                filters: &filters,
+                ..*self
+            },
+        )
+        .await
+    }
+}
+
+type EventStream = Pin<
+    Box<dyn Stream<Item = Result<bollard::models::EventMessage, bollard::errors::Error>> + Send>,
+>;
+
+fn subscribe<C: Watch>(docker: &Docker) -> EventStream {
+    let actions = C::SCHEDULE_EVENTS
+        .iter()
+        .chain(&["destroy"])
+        .map(|action| action.to_string())
+        .collect();
+    let filters = HashMap::from([
+        ("type".to_string(), vec![C::EVENT_TYPE.to_string()]),
+        ("event".to_string(), actions),
+    ]);
+    docker
+        .events(Some(EventsOptions {
+            filters: Some(filters),
+            ..Default::default()
+        }))
+        .boxed()
+}
+
+/// Converts an expiration time into the instant at which the resource should be reaped.
+fn deadline(time: DateTime<Utc>) -> Instant {
+    let remaining = (time - Utc::now()).to_std().unwrap_or(Duration::ZERO);
+    Instant::now() + remaining + EXPIRATION_GRACE
+}
+
+/// Bookkeeping of watch mode: the pending expirations of resources, and when to next resync or
+/// resubscribe to the event stream.
+#[derive(Debug)]
+pub(crate) struct Schedule {
+    expirations: HashMap<String, DateTime<Utc>>,
+    resync_interval: Duration,
+    next_resync: Instant,
+    /// When to resubscribe to the event stream, if it failed or ended.
+    resubscribe_at: Option<Instant>,
+}
+
+impl Schedule {
+    /// Creates an empty schedule which is due for a resync immediately.
+    pub(crate) fn new(resync_interval: Duration) -> Self {
+        Self {
+            expirations: HashMap::new(),
+            resync_interval,
+            next_resync: Instant::now(),
+            resubscribe_at: None,
+        }
+    }
+
+    /// Instant at which the next full resync is due.
+    pub(crate) fn next_resync(&self) -> Instant {
+        self.next_resync
+    }
+
+    /// Instant at which to resubscribe to the event stream, if it failed or ended.
+    pub(crate) fn resubscribe_at(&self) -> Option<Instant> {
+        self.resubscribe_at
+    }
+
+    /// The resource which expires next, and the instant at which it should be reaped.
+    pub(crate) fn next_expiration(&self) -> Option<(String, Instant)> {
+        self.expirations
+            .iter()
+            .min_by_key(|(_, time)| **time)
+            .map(|(id, time)| (id.clone(), deadline(*time)))
+    }
+
+    /// Records a full resync. The pending expirations are rebuilt from scratch, unless they could
+    /// not be determined, in which case the previous ones are kept.
+    pub(crate) fn resynced(&mut self, expirations: Option<Vec<Expiration>>) {
+        if let Some(expirations) = expirations {
+            self.expirations = expirations
+                .into_iter()
+                .map(|expiration| (expiration.id, expiration.time))
+                .collect();
+            debug!("Scheduled {} pending expirations", self.expirations.len());
+        }
+        self.next_resync = Instant::now() + self.resync_interval;
+    }
+
+    /// Replaces the pending expirations of the resource with the given ID. The resource may no
+    /// longer expire at all, e.g. once a container was restarted when measuring its age from when
+    /// it exited.
+    pub(crate) fn reschedule(&mut self, id: &str, expirations: Vec<Expiration>) {
+        self.expirations.remove(id);
+        for expiration in expirations {
+            debug!(
+                "Scheduled {} to expire at {}",
+                expiration.id, expiration.time
+            );
+            self.expirations.insert(expiration.id, expiration.time);
+        }
+    }
+
+    /// Removes the resource with the given ID, which expired or was destroyed.
+    pub(crate) fn remove(&mut self, id: &str) {
+        self.expirations.remove(id);
+    }
+
+    /// Records that the event stream failed or ended, delaying resubscription so that a
+    /// persistently failing stream is not retried in a busy loop.
+    pub(crate) fn event_stream_lost(&mut self) {
+        self.resubscribe_at = Some(Instant::now() + RESUBSCRIBE_DELAY);
+    }
+
+    /// Records that the event stream was resubscribed. Since events may have been missed in the
+    /// meantime, a resync is due immediately.
+    pub(crate) fn resubscribed(&mut self) {
+        self.resubscribe_at = None;
+        self.next_resync = Instant::now();
+    }
+}
+
+/// Reaps resources as they expire, based on the Docker events stream.
+///
+/// Every `resync_interval`, all matching resources are reaped and the schedule of pending
+/// expirations is rebuilt from scratch. In between, created resources are added to the schedule
+/// as reported by the events stream and reaped individually once they expire.
+pub(crate) async fn watch<C: Watch>(
+    docker: &Docker,
+    config: &C,
+    resync_interval: Duration,
+    reporter: &mut Reporter,
+) {
+    let mut schedule = Schedule::new(resync_interval);
+    let mut events = subscribe::<C>(docker);
+    loop {
+        let next_expiration = schedule.next_expiration();
+        let next_resync = schedule.next_resync();
+        let resubscribe_at = schedule.resubscribe_at();
+        tokio::select! {
+            _ = sleep_until(next_resync) => {
+                info!("Resyncing {} resources", C::EVENT_TYPE);
+                let started = Instant::now();
+                let result = config.reap(docker, None).await;
+                reporter.report(result, None, started.elapsed());
+                let expirations = match config.expirations(docker, None).await {
+                    Ok(expirations) => Some(expirations),
+                    Err(e) => {
+                        warn!("Failed to determine pending expirations: {}", e);
+                        None
+                    }
+                };
+                schedule.resynced(expirations);
+            }
+            _ = sleep_until(next_expiration.as_ref().map_or(next_resync, |(_, at)| *at)),
+                if next_expiration.is_some() =>
+            {
+                let Some((id, _)) = next_expiration else {
+                    continue;
+                };
+                schedule.remove(&id);
+                info!("Reaping expired {} {}", C::EVENT_TYPE, id);
+                let started = Instant::now();
+                let result = config.reap(docker, Some(&id)).await;
+                reporter.report(result, None, started.elapsed());
+            }
+            _ = sleep_until(resubscribe_at.unwrap_or(next_resync)), if resubscribe_at.is_some() => {
+                events = subscribe::<C>(docker);
+                schedule.resubscribed();
+            }
+            event = events.next() => {
+                let Some(Ok(event)) = event else {
+                    match event {
+                        Some(Err(e)) => warn!("Docker event stream failed: {}", e),
+                        _ => warn!("Docker event stream ended"),
+                    }
+                    // Expirations and resyncs continue to be handled until resubscribing
+                    events = futures::stream::pending().boxed();
+                    schedule.event_stream_lost();
+                    continue;
+                };
+                let Some(id) = event.actor.and_then(|actor| actor.id) else {
+                    continue;
+                };
+                match event.action.as_deref() {
+                    Some(action) if C::SCHEDULE_EVENTS.contains(&action) => {
+                        match config.expirations(docker, Some(&id)).await {
+                            Ok(expirations) => schedule.reschedule(&id, expirations),
+                            Err(e) => warn!(
+                                "Failed to determine expiration of {} {}: {}",
+                                C::EVENT_TYPE, id, e
+                            ),
+                        }
+                    }
+                    Some("destroy") => {
+                        schedule.remove(&id);
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+}